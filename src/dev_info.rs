@@ -1,4 +1,4 @@
-use crate::{game::Game, render::Render};
+use crate::{game::Game, launcher::Launcher, render::Render};
 use macroquad::prelude::*;
 
 pub struct DevInfo {
@@ -101,7 +101,7 @@ impl DevInfo {
                         .terrain_index_beneath(render.render_space.position)
                 )
             ),
-            format!("{}", game.trebuchet.w_projectile()),
+            format!("{}", game.engine.projectile_velocity().length()),
         ];
 
         let mut spacing = screen_height() + 10.0 - 20.0 * dev_info.len() as f32;
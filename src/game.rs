@@ -1,27 +1,37 @@
 use macroquad::prelude::*;
 
 use crate::utils::get_intersection;
+use launcher::*;
 use player::*;
+use replay::*;
 use resources::*;
 use settings::*;
 use stats::*;
 use tech::*;
-use trebuchet::*;
+use trebuchet::blueprint::{Blueprint, BlueprintError};
+use upgrades::Shot;
 use world::*;
 
 use crate::GameError;
 // use upgrades::*;
 
+pub mod floating_arm;
+pub mod launcher;
+pub mod mangonel;
+pub mod orbit;
 pub mod player;
+mod replay;
 mod resources;
 mod settings;
-mod stats;
+pub mod stats;
 mod tech;
 pub mod trebuchet;
-mod upgrades;
+pub mod upgrades;
 pub mod world;
 
 const START_POINT: I64Vec2 = i64vec2(0, 1_631_092_934);
+// Where along its orbit the Bmoon is parked, a little ahead of the launch site
+const BMOON_ANGLE: f32 = 1.2;
 
 #[derive(PartialEq, Debug)]
 pub enum GameState {
@@ -34,20 +44,37 @@ pub enum GameState {
 pub struct Game {
     pub state: GameState,
 
-    pub day:   u32,
-    pub stats: Stats,
+    pub day:    u32,
+    pub stats:  Stats,
+    pub replay: Replay,
+    /// Selected time warp level, index into `WARP_LEVELS`
+    pub warp:   usize,
 
     pub world:     World,
-    pub trebuchet: Trebuchet,
+    pub moons:     Vec<World>,
+    pub engine:    SiegeEngine,
+    /// Engines swapped out with `switch_engine`, kept with their designs
+    pub parked:    Vec<SiegeEngine>,
     pub player:    Player,
+    pub shots:     Vec<Shot>,
     pub resources: Resources,
     pub tech_tree: TechTree,
+    pub blueprints: Vec<Blueprint>,
 
     pub settings: Settings,
 }
 
 impl Game {
     pub async fn init() -> Result<Game, GameError> {
+        Ok(Game {
+            tech_tree: TechTree::init().await?,
+            blueprints: vec![Blueprint::load("blueprints/starter.json").await?],
+            ..Game::headless()
+        })
+    }
+
+    /// Game without any loaded assets, enough to run the simulation in tests
+    pub fn headless() -> Game {
         let world = World::new(
             1.0,
             I64Vec2::ZERO,
@@ -57,24 +84,30 @@ impl Game {
             None,
         );
 
-        let mut trebuchet = Trebuchet::init(START_POINT).build();
-        trebuchet.reset();
-        let player = Player::new(trebuchet.projectile_position());
+        let moons = vec![bmoon(&world, 1.0)];
+        let engine = SiegeEngine::new(EngineKind::Trebuchet, START_POINT);
+        let player = Player::new(engine.projectile_position());
 
-        Ok(Game {
+        Game {
             state: GameState::Paused,
 
             day: 0,
             stats: Stats::default(),
+            replay: Replay::default(),
+            warp: 0,
 
             world,
-            trebuchet,
+            moons,
+            engine,
+            parked: Vec::new(),
             player,
+            shots: Vec::new(),
             resources: Resources::default(),
-            tech_tree: TechTree::init().await?,
+            tech_tree: TechTree::default(),
+            blueprints: Vec::new(),
 
             settings: Settings::default(),
-        })
+        }
     }
 
     pub fn new_game(&mut self) {
@@ -100,24 +133,152 @@ impl Game {
             WorldClass::Minshara,
             Some(&terra),
         );
+        self.moons = vec![bmoon(&self.world, self.settings.scale)];
 
         let terrain_idx = self.world.terrain_index_beneath(START_POINT);
         let a = self.world.surface(terrain_idx);
         let b = self.world.surface(terrain_idx + 1);
-        self.trebuchet.position =
-            get_intersection(a, b, START_POINT, self.world.position).unwrap();
-        self.player.position = self.trebuchet.projectile_position();
+        self.engine
+            .set_position(get_intersection(a, b, START_POINT, self.world.position).unwrap());
+        self.settle_wheels();
+        self.player.position = self.engine.projectile_position();
 
         self.state = GameState::PreLaunch;
     }
 
+    /// Lets go of the trebuchet, the beans packed as fuel come out of the stockpile
+    pub fn launch(&mut self) {
+        // Beans only burn with something to burn them in
+        if self.player.thrusters.is_some() {
+            self.resources.beans -= self.player.beans.min(self.resources.beans);
+        }
+        self.state = GameState::Launched;
+    }
+
     pub fn next_day(&mut self) {
         self.resources.research += self.stats.crunch();
         self.stats = Stats::default();
+        self.replay.clear();
+        self.shots.clear();
+        self.warp = 0;
         self.state = GameState::PreLaunch;
-        self.trebuchet.reset();
-        self.player.position = self.trebuchet.projectile_position();
+        self.engine.reset();
+        self.player.position = self.engine.projectile_position();
         self.player.rotation = 0.0;
+        self.player.temperature = AMBIENT_TEMPERATURE;
+        self.player.load_beans(self.player.beans.min(self.resources.beans));
+        self.player.pack_brakes();
+        self.player.burrow = 0.0;
         self.day += 1;
     }
+
+    /// Planet or moon whose gravity rules at `point`
+    pub fn body_at(&self, point: I64Vec2) -> &World {
+        body_at(&self.world, &self.moons, point)
+    }
+
+    /// Rolling resistance and downrange tangent of the ground at `point`
+    pub fn ground_at(&self, point: I64Vec2) -> (f32, Vec2) {
+        let index = self.world.terrain_index_beneath(point);
+        let tangent = -self.world.surface_normal(point).perp();
+        (self.world.terrain_class(index).rolling_resistance(), tangent)
+    }
+
+    /// Wheels pick up the ground beneath wherever the base has rolled to
+    pub fn settle_wheels(&mut self) {
+        let SiegeEngine::Trebuchet(trebuchet) = &self.engine else {
+            return;
+        };
+        if trebuchet.wheels.is_none() {
+            return;
+        }
+        let (resistance, tangent) = self.ground_at(trebuchet.base_point());
+        if let SiegeEngine::Trebuchet(trebuchet) = &mut self.engine {
+            trebuchet.set_ground(resistance, tangent);
+        }
+    }
+
+    /// Swaps in a trebuchet built from `blueprint` where the current engine stands,
+    /// paying for the rebuild if it is in another material
+    pub fn load_blueprint(&mut self, blueprint: &Blueprint) -> Result<(), GameError> {
+        blueprint.check_unlocked(|tech| self.tech_tree.have(tech))?;
+        let mut trebuchet = blueprint.builder(self.engine.position())?.build();
+        let cost = match &self.engine {
+            SiegeEngine::Trebuchet(current) if current.material == trebuchet.material => 0,
+            _ => trebuchet.material.cost(),
+        };
+        if self.resources.rocks < cost {
+            return Err(BlueprintError::Unaffordable(cost).into());
+        }
+        self.resources.rocks -= cost;
+        trebuchet.reset();
+        self.engine = SiegeEngine::Trebuchet(trebuchet);
+        self.settle_wheels();
+        self.player.position = self.engine.projectile_position();
+        Ok(())
+    }
+
+    /// Parks the current engine and rolls out one of `kind`, as it was left if it was parked before
+    pub fn switch_engine(&mut self, kind: EngineKind) {
+        let position = self.engine.position();
+        let mut engine = match self.parked.iter().position(|engine| engine.kind() == kind) {
+            Some(index) => self.parked.swap_remove(index),
+            None => SiegeEngine::new(kind, position),
+        };
+        engine.set_position(position);
+        engine.reset();
+        self.parked.push(std::mem::replace(&mut self.engine, engine));
+        self.settle_wheels();
+        self.player.position = self.engine.projectile_position();
+    }
+}
+
+fn bmoon(planet: &World, scale: f32) -> World {
+    World::new(
+        scale,
+        I64Vec2::ZERO,
+        1_737_400.0,
+        7.342e+22,
+        WorldClass::Barren,
+        None,
+    )
+    .orbiting(planet, 384_400_000.0 * scale, BMOON_ANGLE)
+}
+
+#[cfg(test)]
+mod game_test {
+    use super::*;
+    use upgrades::Thruster;
+
+    #[test]
+    fn switch_keeps_design() {
+        let mut game = Game::headless();
+        let SiegeEngine::Trebuchet(trebuchet) = &mut game.engine else {
+            panic!("starts with a trebuchet");
+        };
+        trebuchet.sling.release_angle = 0.3;
+
+        game.switch_engine(EngineKind::FloatingArm);
+        assert_eq!(game.engine.kind(), EngineKind::FloatingArm);
+        game.switch_engine(EngineKind::Trebuchet);
+        let SiegeEngine::Trebuchet(trebuchet) = &game.engine else {
+            panic!("switched back to the trebuchet");
+        };
+        assert_eq!(trebuchet.sling.release_angle, 0.3);
+        assert_eq!(game.parked.len(), 1);
+    }
+
+    #[test]
+    fn beans_need_a_thruster() {
+        let mut game = Game::headless();
+        game.resources.beans = 50;
+        game.player.load_beans(20);
+        game.launch();
+        assert_eq!(game.resources.beans, 50);
+
+        game.state = GameState::PreLaunch;
+        game.player.thrusters = Some(Thruster::Bean);
+        game.launch();
+        assert_eq!(game.resources.beans, 30);
+    }
 }
@@ -0,0 +1,198 @@
+use core::f32::consts;
+use macroquad::math::*;
+
+use super::launcher::{cocked_angle, Launcher, GRAVITY};
+use crate::utils::*;
+
+// Step for the numerical derivatives of the mass and height terms
+const DERIVATIVE_STEP: f32 = 1.0e-3;
+
+/// Arm with no fixed pivot, the counterweight drops straight down a guide on the short end
+/// while the axle rolls along a level track, throws from a cup once the arm swings upright
+#[derive(Clone)]
+pub struct FloatingArm {
+    pub position:     I64Vec2,
+    /// Height of the track the axle rolls on
+    pub height:       Meters,
+    pub long_length:  Meters,
+    pub short_length: Meters,
+    arm_mass:         Kilograms,
+    pub weight_mass:  Kilograms,
+    m_proj:           Kilograms,
+
+    /// Arm angle from vertical where the cup lets go
+    pub release_angle: f32,
+
+    pub angle: f32,
+    velocity:  f32,
+    released:  bool,
+}
+
+impl FloatingArm {
+    pub fn init(position: I64Vec2) -> FloatingArm {
+        FloatingArm {
+            position,
+            height:        1.0,
+            long_length:   2.0,
+            short_length:  0.5,
+            arm_mass:      0.5,
+            weight_mass:   50.0,
+            m_proj:        0.3,
+            release_angle: consts::FRAC_PI_6,
+            angle:         0.0,
+            velocity:      0.0,
+            released:      false,
+        }
+    }
+
+    fn direction(angle: f32) -> Vec2 {
+        Vec2::from_angle(angle).rotate(Vec2::Y)
+    }
+
+    /// Counterweight on the short end, held on the guide above the base
+    pub fn weight_point_at(&self, angle: f32) -> Vec2 {
+        vec2(0.0, self.height - self.short_length * angle.cos())
+    }
+
+    pub fn axle_point_at(&self, angle: f32) -> Vec2 {
+        self.weight_point_at(angle) + Self::direction(angle) * self.short_length
+    }
+
+    pub fn cup_point_at(&self, angle: f32) -> Vec2 {
+        self.weight_point_at(angle) + Self::direction(angle) * (self.short_length + self.long_length)
+    }
+
+    fn center_point_at(&self, angle: f32) -> Vec2 {
+        self.weight_point_at(angle)
+            + Self::direction(angle) * (self.short_length + self.long_length) / 2.0
+    }
+
+    pub fn weight_point(&self) -> Vec2 {
+        self.weight_point_at(self.angle)
+    }
+
+    pub fn axle_point(&self) -> Vec2 {
+        self.axle_point_at(self.angle)
+    }
+
+    pub fn cup_point(&self) -> Vec2 {
+        self.cup_point_at(self.angle)
+    }
+
+    fn m_cup(&self) -> Kilograms {
+        match self.released {
+            true => 0.0,
+            false => self.m_proj,
+        }
+    }
+
+    /// Generalized mass of the one degree of freedom, every part moves with the arm angle
+    fn mass(&self, angle: f32) -> f32 {
+        let rate = |point: fn(&Self, f32) -> Vec2| {
+            (point(self, angle + DERIVATIVE_STEP) - point(self, angle - DERIVATIVE_STEP))
+                / (2.0 * DERIVATIVE_STEP)
+        };
+        let inertia = self.arm_mass * (self.short_length + self.long_length).powi(2) / 12.0;
+        inertia
+            + self.weight_mass * rate(Self::weight_point_at).length_squared()
+            + self.arm_mass * rate(Self::center_point_at).length_squared()
+            + self.m_cup() * rate(Self::cup_point_at).length_squared()
+    }
+
+    fn potential(&self, angle: f32) -> f32 {
+        GRAVITY
+            * (self.weight_mass * self.weight_point_at(angle).y
+                + self.arm_mass * self.center_point_at(angle).y
+                + self.m_cup() * self.cup_point_at(angle).y)
+    }
+
+    fn derivative(&self, _t: f32, state: Vec2) -> Vec2 {
+        let Vec2 { x: angle, y: velocity } = state;
+        let slope = |f: fn(&Self, f32) -> f32| {
+            (f(self, angle + DERIVATIVE_STEP) - f(self, angle - DERIVATIVE_STEP))
+                / (2.0 * DERIVATIVE_STEP)
+        };
+        // Lagrange with a mass that changes with the angle
+        let accel = -(0.5 * slope(Self::mass) * velocity.powi(2) + slope(Self::potential))
+            / self.mass(angle);
+        vec2(velocity, accel)
+    }
+}
+
+impl Launcher for FloatingArm {
+    fn advance(&mut self, dt: f32) -> bool {
+        if self.released {
+            return true;
+        }
+
+        let state = rk5(vec2(self.angle, self.velocity), dt, |t, x| {
+            self.derivative(t, x)
+        });
+        Vec2 { x: self.angle, y: self.velocity } = state;
+
+        // Weight lands on its catch as the cup lets go
+        if self.angle <= self.release_angle {
+            self.velocity = 0.0;
+            self.released = true;
+        }
+        self.released
+    }
+
+    fn released(&self) -> bool {
+        self.released
+    }
+
+    fn reset(&mut self) {
+        self.angle = cocked_angle(self.height, self.long_length);
+        self.velocity = 0.0;
+        self.released = false;
+    }
+
+    fn position(&self) -> I64Vec2 {
+        self.position
+    }
+
+    fn set_position(&mut self, position: I64Vec2) {
+        self.position = position;
+    }
+
+    fn projectile_position(&self) -> I64Vec2 {
+        to_i64coords(self.cup_point()) + self.position
+    }
+
+    fn projectile_velocity(&self) -> Vec2 {
+        let rate = (self.cup_point_at(self.angle + DERIVATIVE_STEP)
+            - self.cup_point_at(self.angle - DERIVATIVE_STEP))
+            / (2.0 * DERIVATIVE_STEP);
+        rate * self.velocity
+    }
+
+    fn projectile_rotation(&self) -> f32 {
+        Self::direction(self.angle).to_angle()
+    }
+}
+
+#[cfg(test)]
+mod floating_arm_test {
+    use super::{FloatingArm, Launcher};
+    use macroquad::math::I64Vec2;
+
+    #[test]
+    fn throws_forward() {
+        let mut engine = FloatingArm::init(I64Vec2::ZERO);
+        engine.reset();
+        assert!(engine.cup_point().y.abs() < 1e-4);
+        let mut velocity = engine.projectile_velocity();
+        let mut ticks = 0;
+        while !engine.advance(0.001) {
+            velocity = engine.projectile_velocity();
+            // Weight never swings through the ground
+            assert!(engine.weight_point().y >= 0.0);
+            // Axle stays on its track
+            assert!((engine.axle_point().y - engine.height).abs() < 1e-4);
+            ticks += 1;
+            assert!(ticks < 10_000);
+        }
+        assert!(velocity.x > 0.0 && velocity.y > 0.0, "{velocity:?}");
+    }
+}
@@ -0,0 +1,139 @@
+use core::fmt;
+use macroquad::math::{I64Vec2, Vec2};
+
+use super::floating_arm::FloatingArm;
+use super::mangonel::Mangonel;
+use super::trebuchet::Trebuchet;
+use crate::utils::Meters;
+
+pub const GRAVITY: f32 = 9.81;
+
+/// Arm angle from vertical with the cup resting on the ground behind the frame,
+/// where engines without a sling are cocked
+pub fn cocked_angle(height: Meters, arm_length: Meters) -> f32 {
+    (-height / arm_length).acos()
+}
+
+/// Common interface for anything that can throw the plushie
+pub trait Launcher {
+    /// Steps the engine by `dt`, returns true once the projectile is released
+    fn advance(&mut self, dt: f32) -> bool;
+    fn released(&self) -> bool;
+    fn reset(&mut self);
+
+    fn position(&self) -> I64Vec2;
+    fn set_position(&mut self, position: I64Vec2);
+
+    fn projectile_position(&self) -> I64Vec2;
+    fn projectile_velocity(&self) -> Vec2;
+    fn projectile_rotation(&self) -> f32;
+
+    fn breakage(&self) -> Option<Breakage> {
+        None
+    }
+}
+
+/// Part that gave out under load during a launch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakage {
+    ArmSnapped,
+    SlingSnapped,
+}
+
+impl fmt::Display for Breakage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakage::ArmSnapped => write!(f, "Arm snapped!"),
+            Breakage::SlingSnapped => write!(f, "Sling snapped!"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineKind {
+    Trebuchet,
+    Mangonel,
+    FloatingArm,
+}
+
+#[derive(Clone)]
+pub enum SiegeEngine {
+    Trebuchet(Trebuchet),
+    Mangonel(Mangonel),
+    FloatingArm(FloatingArm),
+}
+
+impl SiegeEngine {
+    /// Fresh engine of the given kind at rest on `position`
+    pub fn new(kind: EngineKind, position: I64Vec2) -> SiegeEngine {
+        let mut engine = match kind {
+            EngineKind::Trebuchet => SiegeEngine::Trebuchet(Trebuchet::init(position).build()),
+            EngineKind::Mangonel => SiegeEngine::Mangonel(Mangonel::init(position)),
+            EngineKind::FloatingArm => SiegeEngine::FloatingArm(FloatingArm::init(position)),
+        };
+        engine.reset();
+        engine
+    }
+
+    pub fn kind(&self) -> EngineKind {
+        match self {
+            SiegeEngine::Trebuchet(_) => EngineKind::Trebuchet,
+            SiegeEngine::Mangonel(_) => EngineKind::Mangonel,
+            SiegeEngine::FloatingArm(_) => EngineKind::FloatingArm,
+        }
+    }
+
+    fn launcher(&self) -> &dyn Launcher {
+        match self {
+            SiegeEngine::Trebuchet(trebuchet) => trebuchet,
+            SiegeEngine::Mangonel(mangonel) => mangonel,
+            SiegeEngine::FloatingArm(floating_arm) => floating_arm,
+        }
+    }
+
+    fn launcher_mut(&mut self) -> &mut dyn Launcher {
+        match self {
+            SiegeEngine::Trebuchet(trebuchet) => trebuchet,
+            SiegeEngine::Mangonel(mangonel) => mangonel,
+            SiegeEngine::FloatingArm(floating_arm) => floating_arm,
+        }
+    }
+}
+
+impl Launcher for SiegeEngine {
+    fn advance(&mut self, dt: f32) -> bool {
+        self.launcher_mut().advance(dt)
+    }
+
+    fn released(&self) -> bool {
+        self.launcher().released()
+    }
+
+    fn reset(&mut self) {
+        self.launcher_mut().reset()
+    }
+
+    fn position(&self) -> I64Vec2 {
+        self.launcher().position()
+    }
+
+    fn set_position(&mut self, position: I64Vec2) {
+        self.launcher_mut().set_position(position)
+    }
+
+    fn projectile_position(&self) -> I64Vec2 {
+        self.launcher().projectile_position()
+    }
+
+    fn projectile_velocity(&self) -> Vec2 {
+        self.launcher().projectile_velocity()
+    }
+
+    fn projectile_rotation(&self) -> f32 {
+        self.launcher().projectile_rotation()
+    }
+
+    fn breakage(&self) -> Option<Breakage> {
+        self.launcher().breakage()
+    }
+}
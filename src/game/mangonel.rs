@@ -0,0 +1,126 @@
+use core::f32::consts;
+use macroquad::math::*;
+
+use super::launcher::{cocked_angle, Launcher, GRAVITY};
+use crate::utils::*;
+
+/// Torsion powered arm with a cup, throws when the arm slams into the stop bar
+#[derive(Clone)]
+pub struct Mangonel {
+    pub position:   I64Vec2,
+    pub height:     Meters,
+    pub arm_length: Meters,
+    arm_mass:       Kilograms,
+    m_proj:         Kilograms,
+
+    /// Torque per radian of twist, the bundle is untwisted with the arm upright
+    pub stiffness:  f32,
+    /// Arm angle from vertical where the stop bar catches it
+    pub stop_angle: f32,
+
+    pub angle: f32,
+    velocity:  f32,
+    released:  bool,
+}
+
+impl Mangonel {
+    pub fn init(position: I64Vec2) -> Mangonel {
+        Mangonel {
+            position,
+            height:     1.0,
+            arm_length: 2.0,
+            arm_mass:   0.5,
+            m_proj:     0.3,
+            stiffness:  100.0,
+            stop_angle: consts::FRAC_PI_4,
+            angle:      0.0,
+            velocity:   0.0,
+            released:   false,
+        }
+    }
+
+    pub fn cup_point(&self) -> Vec2 {
+        Vec2::from_angle(self.angle).rotate(Vec2::Y * self.arm_length)
+    }
+
+    fn derivative(&self, _t: f32, state: Vec2) -> Vec2 {
+        let Vec2 { x: angle, y: velocity } = state;
+        let cga = self.arm_length / 2.0;
+        let inertia = self.arm_mass * self.arm_length.powi(2) / 3.0
+            + self.m_proj * self.arm_length.powi(2);
+        let torque = -self.stiffness * angle
+            + GRAVITY * (self.arm_mass * cga + self.m_proj * self.arm_length) * angle.sin();
+        vec2(velocity, torque / inertia)
+    }
+}
+
+impl Launcher for Mangonel {
+    fn advance(&mut self, dt: f32) -> bool {
+        if self.released {
+            return true;
+        }
+
+        let state = rk5(vec2(self.angle, self.velocity), dt, |t, x| {
+            self.derivative(t, x)
+        });
+        Vec2 { x: self.angle, y: self.velocity } = state;
+
+        if self.angle <= self.stop_angle {
+            self.angle = self.stop_angle;
+            self.velocity = 0.0;
+            self.released = true;
+        }
+        self.released
+    }
+
+    fn released(&self) -> bool {
+        self.released
+    }
+
+    fn reset(&mut self) {
+        self.angle = cocked_angle(self.height, self.arm_length);
+        self.velocity = 0.0;
+        self.released = false;
+    }
+
+    fn position(&self) -> I64Vec2 {
+        self.position
+    }
+
+    fn set_position(&mut self, position: I64Vec2) {
+        self.position = position;
+    }
+
+    fn projectile_position(&self) -> I64Vec2 {
+        to_i64coords(self.cup_point() + Vec2::Y * self.height) + self.position
+    }
+
+    fn projectile_velocity(&self) -> Vec2 {
+        self.cup_point().perp() * self.velocity
+    }
+
+    fn projectile_rotation(&self) -> f32 {
+        self.cup_point().to_angle()
+    }
+}
+
+#[cfg(test)]
+mod mangonel_test {
+    use super::{Launcher, Mangonel};
+    use macroquad::math::I64Vec2;
+
+    #[test]
+    fn throws_forward() {
+        let mut mangonel = Mangonel::init(I64Vec2::ZERO);
+        mangonel.reset();
+        let mut velocity = mangonel.projectile_velocity();
+        let mut ticks = 0;
+        while !mangonel.advance(0.001) {
+            velocity = mangonel.projectile_velocity();
+            ticks += 1;
+            assert!(ticks < 10_000);
+        }
+        assert!(mangonel.released());
+        assert!(velocity.x > 0.0 && velocity.y > 0.0);
+    }
+}
@@ -0,0 +1,115 @@
+use core::{f64::consts, fmt};
+use macroquad::math::*;
+
+use super::world::World;
+use crate::utils::*;
+
+/// Where the current path is headed if nothing else pushes on it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trajectory {
+    /// Periapsis is inside the atmosphere or the ground, comes back down
+    Suborbital,
+    /// Closed path clear of the atmosphere
+    Orbit,
+    Escape,
+}
+
+impl fmt::Display for Trajectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trajectory::Suborbital => write!(f, "Suborbital"),
+            Trajectory::Orbit => write!(f, "Orbit"),
+            Trajectory::Escape => write!(f, "Escape"),
+        }
+    }
+}
+
+/// Keplerian elements of a two body orbit around a world, distances from its center.
+/// Computed in f64, f32 loses near circular eccentricities at real scale.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    /// Negative for escape trajectories
+    pub semi_major_axis: Meters,
+    pub eccentricity:    f32,
+    pub periapsis:       Meters,
+    /// Only bound orbits turn around
+    pub apoapsis:        Option<Meters>,
+    pub period:          Option<f32>,
+}
+
+impl OrbitalElements {
+    /// Elements from position relative to the world center, velocity and G*M
+    pub fn new(position: Vec2, velocity: Vec2, mu: f32) -> OrbitalElements {
+        let (r, v, mu) = (position.as_dvec2(), velocity.as_dvec2(), mu as f64);
+        let energy = v.length_squared() / 2.0 - mu / r.length();
+        let momentum = r.perp_dot(v);
+        let eccentricity = (1.0 + 2.0 * energy * momentum.powi(2) / mu.powi(2))
+            .max(0.0)
+            .sqrt();
+        let semi_major_axis = -mu / (2.0 * energy);
+        // Semi-latus rectum stays finite for parabolic paths
+        let periapsis = momentum.powi(2) / mu / (1.0 + eccentricity);
+
+        // Straight up throws are degenerate ellipses with eccentricity 1, go by energy
+        let bound = energy < 0.0;
+        OrbitalElements {
+            semi_major_axis: semi_major_axis as f32,
+            eccentricity:    eccentricity as f32,
+            periapsis:       periapsis as f32,
+            apoapsis:        bound.then_some((semi_major_axis * (1.0 + eccentricity)) as f32),
+            period:          bound.then_some(
+                (2.0 * consts::PI * (semi_major_axis.powi(3) / mu).sqrt()) as f32,
+            ),
+        }
+    }
+
+    /// Elements of whatever is at `position` moving at `velocity` around `world`
+    pub fn around(world: &World, position: I64Vec2, velocity: Vec2) -> OrbitalElements {
+        OrbitalElements::new(
+            to_meters(position - world.position),
+            velocity,
+            world.gravitational_parameter(),
+        )
+    }
+
+    pub fn trajectory(&self, world: &World) -> Trajectory {
+        match self.apoapsis {
+            None => Trajectory::Escape,
+            Some(_) if self.periapsis > world.radius + world.atmosphere_height() => {
+                Trajectory::Orbit
+            }
+            Some(_) => Trajectory::Suborbital,
+        }
+    }
+}
+
+#[cfg(test)]
+mod orbit_test {
+    use super::*;
+
+    const MU: f32 = 3.986e14;
+    const RADIUS: f32 = 6.771e6;
+
+    #[test]
+    fn circular() {
+        let speed = (MU / RADIUS).sqrt();
+        let orbit = OrbitalElements::new(Vec2::X * RADIUS, Vec2::Y * speed, MU);
+        assert!(orbit.eccentricity < 1e-3, "{orbit:?}");
+        assert!((orbit.periapsis / RADIUS - 1.0).abs() < 1e-3);
+        assert!((orbit.apoapsis.unwrap() / RADIUS - 1.0).abs() < 1e-3);
+        // Low orbit goes around in about an hour and a half
+        assert!((orbit.period.unwrap() - 5_544.0).abs() < 10.0, "{orbit:?}");
+    }
+
+    #[test]
+    fn escape_and_fall() {
+        let escape = (2.0 * MU / RADIUS).sqrt() * 1.01;
+        let orbit = OrbitalElements::new(Vec2::X * RADIUS, Vec2::Y * escape, MU);
+        assert!(orbit.eccentricity > 1.0 && orbit.apoapsis.is_none());
+
+        // Straight up throw never gets anywhere sideways
+        let orbit = OrbitalElements::new(Vec2::X * RADIUS, Vec2::X * 1000.0, MU);
+        assert!(orbit.periapsis < 1.0);
+        assert!(orbit.apoapsis.unwrap() > RADIUS);
+    }
+}
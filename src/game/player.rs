@@ -1,6 +1,44 @@
 use super::upgrades::*;
+use crate::utils::*;
 use macroquad::math::{I64Vec2, Vec2};
 
+// Tumbling plushie is a rough sphere about 20cm across
+pub const PLUSHIE_RADIUS: f32 = 0.1;
+const DRAG_COEFFICIENT: f32 = 0.8;
+const CROSS_SECTION: f32 = 0.03;
+// Spinning through air loses spin like a rough ball
+const SPIN_DAMPING: f32 = 0.5;
+// Fins sit this far behind the centre of mass
+const FIN_ARM: f32 = 0.15;
+// Jets ease off below this spin so they don't chatter around zero, rad/s
+const JET_DEADBAND: f32 = 0.5;
+
+/// Plushie without any beans in it, kg
+pub const DRY_MASS: f32 = 0.3;
+/// One bean of fuel, kg
+const BEAN_MASS: f32 = 0.001;
+const STANDARD_GRAVITY: f32 = 9.80665;
+
+// Drag coefficient times canopy area of the parachute
+const CHUTE_AREA: f32 = 1.5;
+// Dynamic pressure that rips the canopy if deployed into it, Pa
+const CHUTE_LIMIT: f32 = 1500.0;
+const RETRO_THRUST: f32 = 10.0;
+// Seconds of burn in the retro-rocket
+const RETRO_BURN: f32 = 2.0;
+// Retro-rocket throttles down below this speed instead of pushing the plushie back up
+const RETRO_CUTOFF: f32 = 1.0;
+
+pub const AMBIENT_TEMPERATURE: f32 = 288.0;
+// Bare polyester stuffing starts to melt
+const HEAT_LIMIT: f32 = 520.0;
+// Share of the air's kinetic energy flux that ends up as heat in the plushie
+const HEAT_TRANSFER: f32 = 1.0e-3;
+// J/K of the whole plushie
+const HEAT_CAPACITY: f32 = 400.0;
+const EMISSIVITY: f32 = 0.9;
+const STEFAN_BOLTZMANN: f32 = 5.67e-8;
+
 #[derive(Default)]
 pub struct Player {
     pub position:     I64Vec2,
@@ -11,24 +49,306 @@ pub struct Player {
     pub velocity:     Vec2,
     pub acceleration: Vec2,
 
-    pub gun:   Option<Gun>,
-    pub melee: Option<Melee>,
+    pub gun:    Option<Gun>,
+    pub ammo:   u32,
+    /// Seconds until the gun can fire again
+    pub reload: f32,
+    pub melee:  Option<Melee>,
+    /// Speed of the screw digging under the surface, anticlockwise positive, zero above ground
+    pub burrow: f32,
 
     pub coating:   Option<Coating>,
     pub brakes:    Option<Brakes>,
     pub thrusters: Option<Thruster>,
     pub stablizer: Option<Stabilizer>,
 
-    pub move_speed: f32,
+    pub brake_state: BrakeState,
+    /// Seconds of burn left in the retro-rocket
+    pub retro_burn:  f32,
+
+    /// Beans packed as fuel for the next launch
+    pub beans: u32,
+    /// Fuel left on board, kg
+    pub fuel:  f32,
+    /// Kelvin
+    pub temperature: f32,
 }
 
 impl Player {
     pub fn new(position: I64Vec2) -> Self {
         Self {
             position,
-            mass: 0.3,
-            move_speed: 1000.0,
+            mass: DRY_MASS,
+            temperature: AMBIENT_TEMPERATURE,
+            retro_burn: RETRO_BURN,
             ..Default::default()
         }
     }
+
+    /// Packs `beans` as fuel, topping the mass up to match
+    pub fn load_beans(&mut self, beans: u32) {
+        self.beans = beans;
+        self.fuel = beans as f32 * BEAN_MASS;
+        self.mass = DRY_MASS + self.fuel;
+    }
+
+    pub fn can_burn(&self) -> bool {
+        self.thrusters.is_some() && self.fuel > 0.0
+    }
+
+    /// Fires the thruster at `throttle`, negative pushes backwards, and returns the acceleration
+    pub fn burn(&mut self, throttle: f32, dt: f32) -> Vec2 {
+        let Some(thruster) = self.thrusters else {
+            return Vec2::ZERO;
+        };
+        let exhaust_velocity = thruster.isp() * STANDARD_GRAVITY;
+        let burned = (thruster.thrust() * throttle.abs() / exhaust_velocity * dt).min(self.fuel);
+        self.fuel -= burned;
+        self.mass = DRY_MASS + self.fuel;
+        Vec2::from_angle(self.rotation) * throttle.signum() * burned * exhaust_velocity
+            / (dt * self.mass)
+    }
+
+    /// Speed the fuel left can still add, m/s
+    pub fn delta_v(&self) -> f32 {
+        self.thrusters.map_or(0.0, |thruster| {
+            thruster.isp() * STANDARD_GRAVITY * (self.mass / (self.mass - self.fuel)).ln()
+        })
+    }
+
+    pub fn can_fire(&self) -> bool {
+        self.gun.is_some() && self.ammo > 0
+    }
+
+    /// Works the gun for `dt` with the trigger held or not, recoil kicks the plushie backwards
+    pub fn fire(&mut self, trigger: bool, dt: f32) -> Option<Shot> {
+        self.reload = (self.reload - dt).max(0.0);
+        let gun = self.gun?;
+        if !trigger || self.ammo == 0 || self.reload > 0.0 {
+            return None;
+        }
+        self.ammo -= 1;
+        self.reload = gun.fire_interval();
+
+        let facing = Vec2::from_angle(self.rotation);
+        self.velocity -= facing * gun.round_mass() * gun.muzzle_velocity() / self.mass;
+        Some(Shot {
+            position: self.position + to_i64coords(facing * PLUSHIE_RADIUS),
+            velocity: self.velocity + facing * gun.muzzle_velocity(),
+            age:      0.0,
+        })
+    }
+
+    /// Folds the parachute back up and refills the retro-rocket
+    pub fn pack_brakes(&mut self) {
+        self.brake_state = BrakeState::Stowed;
+        self.retro_burn = RETRO_BURN;
+    }
+
+    pub fn deploy_brakes(&mut self) {
+        if self.brakes.is_some() && self.brake_state == BrakeState::Stowed {
+            self.brake_state = BrakeState::Deployed;
+        }
+    }
+
+    /// Slowdown from deployed brakes in air of `density`, tears the chute if it's going too fast
+    pub fn brake(&mut self, density: f32, dt: f32) -> Vec2 {
+        if self.brake_state != BrakeState::Deployed {
+            return Vec2::ZERO;
+        }
+        let speed = self.velocity.length();
+        match self.brakes {
+            Some(Brakes::Parachute) => {
+                let dynamic_pressure = 0.5 * density * speed.powi(2);
+                if dynamic_pressure > CHUTE_LIMIT {
+                    self.brake_state = BrakeState::Torn;
+                    return Vec2::ZERO;
+                }
+                -0.5 * density * CHUTE_AREA * speed * self.velocity / self.mass
+            }
+            Some(Brakes::Rocket) => {
+                self.retro_burn -= dt;
+                if self.retro_burn <= 0.0 {
+                    self.brake_state = BrakeState::Spent;
+                }
+                let throttle = (speed / RETRO_CUTOFF).min(1.0);
+                -self.velocity.normalize_or_zero() * throttle * RETRO_THRUST / self.mass
+            }
+            None => Vec2::ZERO,
+        }
+    }
+
+    /// Drag coefficient times cross-section, m^2
+    pub fn drag_area(&self) -> f32 {
+        let factor = self.coating.map_or(1.0, |coating| coating.drag_factor());
+        DRAG_COEFFICIENT * factor * CROSS_SECTION
+    }
+
+    pub fn heat_limit(&self) -> f32 {
+        self.coating.map_or(HEAT_LIMIT, |coating| coating.heat_limit())
+    }
+
+    pub fn overheated(&self) -> bool {
+        self.temperature > self.heat_limit()
+    }
+
+    /// Heats from the dynamic pressure pushing through the air and radiates the rest away
+    pub fn update_heat(&mut self, density: f32, dt: f32) {
+        let speed = self.velocity.length();
+        let dynamic_pressure = 0.5 * density * speed.powi(2);
+        let heating = HEAT_TRANSFER * dynamic_pressure * speed * CROSS_SECTION;
+        // Radiates from all around, a sphere has four times its cross-section in surface
+        let cooling = EMISSIVITY * STEFAN_BOLTZMANN * 4.0 * CROSS_SECTION
+            * (self.temperature.powi(4) - AMBIENT_TEMPERATURE.powi(4));
+        self.temperature += (heating - cooling) / HEAT_CAPACITY * dt;
+    }
+
+    /// Solid ball of stuffing, kg m^2
+    pub fn moment_of_inertia(&self) -> f32 {
+        0.4 * self.mass * PLUSHIE_RADIUS.powi(2)
+    }
+
+    /// Torque from the air and any stabilizer on the current spin in air of `density`, N m
+    pub fn torque(&self, density: f32) -> f32 {
+        let speed = self.velocity.length();
+        let spin_drag = 0.5 * density * speed * CROSS_SECTION * PLUSHIE_RADIUS.powi(2);
+        let mut torque = -SPIN_DAMPING * spin_drag * self.ang_velocity;
+
+        if let Some(stabilizer) = self.stablizer {
+            let fins = stabilizer.fin_area() * FIN_ARM;
+            // Sine of the angle between the nose and the airflow
+            let slip = self.velocity.normalize_or_zero().perp_dot(Vec2::from_angle(self.rotation));
+            torque -= 0.5 * density * speed * fins * (speed * slip + FIN_ARM * self.ang_velocity);
+            torque -= stabilizer.jet_torque() * (self.ang_velocity / JET_DEADBAND).clamp(-1.0, 1.0);
+        }
+        torque
+    }
+
+    /// Air resistance against the current velocity in air of `density`
+    pub fn drag(&self, density: f32) -> Vec2 {
+        -0.5 * density * self.drag_area() * self.velocity.length() * self.velocity
+    }
+}
+
+#[cfg(test)]
+mod player_test {
+    use super::*;
+
+    #[test]
+    fn heating() {
+        let mut player = Player::new(I64Vec2::ZERO);
+        player.velocity = Vec2::X * 3000.0;
+        (0..500).for_each(|_| player.update_heat(1.225, 0.001));
+        assert!(player.overheated());
+
+        player.coating = Some(Coating::Asbestos);
+        assert!(!player.overheated());
+        for coating in [Coating::Streamlined, Coating::Asbestos] {
+            assert!(coating.heat_limit() >= HEAT_LIMIT);
+        }
+
+        let hot = player.temperature;
+        player.velocity = Vec2::ZERO;
+        (0..60_000).for_each(|_| player.update_heat(1.225, 0.001));
+        assert!(player.temperature < hot - 100.0);
+        assert!(player.temperature > AMBIENT_TEMPERATURE);
+    }
+
+    #[test]
+    fn thrusters() {
+        let mut player = Player::new(I64Vec2::ZERO);
+        player.load_beans(500);
+        assert_eq!(player.burn(1.0, 0.001), Vec2::ZERO);
+        assert_eq!(player.delta_v(), 0.0);
+
+        // Burning everything gives the rocket equation's worth of speed
+        player.thrusters = Some(Thruster::Bean);
+        let budget = player.delta_v();
+        let mut speed = 0.0;
+        while player.can_burn() {
+            speed += player.burn(1.0, 0.001).length() * 0.001;
+        }
+        assert!((speed - budget).abs() < budget * 0.01, "{speed} of {budget}");
+        assert!((player.mass - DRY_MASS).abs() < 1e-6);
+        assert_eq!(player.burn(1.0, 0.001), Vec2::ZERO);
+    }
+
+    #[test]
+    fn recoil() {
+        let mut player = Player::new(I64Vec2::ZERO);
+        player.gun = Some(Gun::Pistol);
+        player.ammo = 2;
+        player.rotation = std::f32::consts::FRAC_PI_2;
+
+        let shot = player.fire(true, 0.001).expect("loaded pistol fires");
+        let kick = Gun::Pistol.round_mass() * Gun::Pistol.muzzle_velocity() / player.mass;
+        assert!((player.velocity - Vec2::NEG_Y * kick).length() < 1e-4);
+        assert!(shot.velocity.y > 300.0);
+
+        // Trigger held, next round waits out the fire interval
+        let fired = (0..1000).filter(|_| player.fire(true, 0.001).is_some()).count();
+        assert_eq!(fired, 1);
+        assert_eq!(player.ammo, 0);
+        assert!(player.fire(true, 1.0).is_none());
+    }
+
+    #[test]
+    fn brakes() {
+        let mut player = Player::new(I64Vec2::ZERO);
+        player.brakes = Some(Brakes::Parachute);
+        player.velocity = Vec2::NEG_Y * 200.0;
+        player.deploy_brakes();
+        assert_eq!(player.brake(1.225, 0.001), Vec2::ZERO);
+        assert_eq!(player.brake_state, BrakeState::Torn);
+
+        player.pack_brakes();
+        player.velocity = Vec2::NEG_Y * 30.0;
+        player.deploy_brakes();
+        for _ in 0..5000 {
+            let slowdown = player.brake(1.225, 0.001) + Vec2::NEG_Y * 9.81;
+            player.velocity += slowdown * 0.001;
+        }
+        assert_eq!(player.brake_state, BrakeState::Deployed);
+        assert!(player.velocity.length() < 3.0, "{}", player.velocity);
+
+        let mut player = Player::new(I64Vec2::ZERO);
+        player.brakes = Some(Brakes::Rocket);
+        player.velocity = Vec2::X * 100.0;
+        player.deploy_brakes();
+        for _ in 0..3000 {
+            let slowdown = player.brake(0.0, 0.001);
+            player.velocity += slowdown * 0.001;
+        }
+        assert_eq!(player.brake_state, BrakeState::Spent);
+        assert!((player.velocity.x - (100.0 - RETRO_THRUST / player.mass * RETRO_BURN)).abs() < 1.0);
+    }
+
+    #[test]
+    fn stabilizers() {
+        let spin = |player: &mut Player, density: f32, ticks: usize| {
+            for _ in 0..ticks {
+                player.ang_velocity += player.torque(density) / player.moment_of_inertia() * 0.001;
+                player.rotation += player.ang_velocity * 0.001;
+            }
+        };
+
+        // Nothing slows a bare plushie in vacuum
+        let mut player = Player::new(I64Vec2::ZERO);
+        player.ang_velocity = 50.0;
+        spin(&mut player, 0.0, 1000);
+        assert_eq!(player.ang_velocity, 50.0);
+
+        player.stablizer = Some(Stabilizer::Rocket);
+        spin(&mut player, 0.0, 3000);
+        assert!(player.ang_velocity.abs() < JET_DEADBAND);
+
+        // Wings turn the nose into the airflow
+        let mut player = Player::new(I64Vec2::ZERO);
+        player.stablizer = Some(Stabilizer::Wings);
+        player.velocity = Vec2::X * 50.0;
+        player.rotation = 1.0;
+        spin(&mut player, 1.225, 3000);
+        assert!(player.rotation.abs() < 0.05, "rotation {}", player.rotation);
+        assert!(player.ang_velocity.abs() < 0.5);
+    }
 }
@@ -0,0 +1,79 @@
+use super::trebuchet::TrebuchetFrame;
+use crate::physics::PHYSICS_TICK;
+
+// Keep recording a little while after release to see the arm swing through
+const TAIL_TICKS: usize = 500;
+
+/// Per tick recording of the firing sequence
+#[derive(Default)]
+pub struct Replay {
+    frames:  Vec<TrebuchetFrame>,
+    release: Option<usize>,
+    /// Playback position in seconds, `None` while not replaying
+    pub cursor: Option<f32>,
+}
+
+impl Replay {
+    pub fn record(&mut self, frame: TrebuchetFrame, released: bool) {
+        if self.finished() {
+            return;
+        }
+        if released && self.release.is_none() {
+            self.release = Some(self.frames.len());
+        }
+        self.frames.push(frame);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.release
+            .is_some_and(|release| self.frames.len() >= release + TAIL_TICKS)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Replay::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 * PHYSICS_TICK
+    }
+
+    pub fn release_time(&self) -> Option<f32> {
+        self.release.map(|release| release as f32 * PHYSICS_TICK)
+    }
+
+    /// Frame under the playback cursor
+    pub fn current(&self) -> Option<&TrebuchetFrame> {
+        let tick = (self.cursor? / PHYSICS_TICK) as usize;
+        self.frames.get(tick.min(self.frames.len().saturating_sub(1)))
+    }
+}
+
+#[cfg(test)]
+mod replay_test {
+    use super::*;
+    use macroquad::math::I64Vec2;
+
+    #[test]
+    fn stops_after_tail() {
+        let frame = TrebuchetFrame {
+            arm:        0.0,
+            weight:     0.0,
+            sling:      0.0,
+            base:       0.0,
+            projectile: I64Vec2::ZERO,
+        };
+        let mut replay = Replay::default();
+        (0..100).for_each(|_| replay.record(frame, false));
+        (0..2 * TAIL_TICKS).for_each(|_| replay.record(frame, true));
+        assert!(replay.finished());
+        assert_eq!(replay.release_time(), Some(100.0 * PHYSICS_TICK));
+        assert!((replay.duration() - (100 + TAIL_TICKS) as f32 * PHYSICS_TICK).abs() < 1e-6);
+
+        replay.cursor = Some(replay.duration() * 2.0);
+        assert!(replay.current().is_some());
+    }
+}
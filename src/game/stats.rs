@@ -1,3 +1,32 @@
+use core::{f32::consts, fmt};
+
+use super::launcher::Breakage;
+
+// Touching down slower than this earns a share of the soft landing bonus, m/s
+const GENTLE_SPEED: f32 = 5.0;
+const GENTLE_BONUS: f32 = 100.0;
+
+/// How the flight ended when it was not a plain landing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    BurnedUp,
+    Splashdown,
+    Orbit,
+    Escape,
+    MoonLanding,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::BurnedUp => write!(f, "Burned up!"),
+            Outcome::Splashdown => write!(f, "Splashdown!"),
+            Outcome::Orbit => write!(f, "Reached orbit!"),
+            Outcome::Escape => write!(f, "Escaped the world!"),
+            Outcome::MoonLanding => write!(f, "Landed on the moon!"),
+        }
+    }
+}
 
 pub struct Stat {
     pub field: String,
@@ -7,22 +36,107 @@ pub struct Stat {
 
 #[derive(Default)]
 pub struct Stats {
-    pub time:         f32,
-    pub distance:     f32,
-    pub max_altitude: f32,
-    pub max_speed:    f32,
+    pub time:            f32,
+    pub distance:        f32,
+    pub max_altitude:    f32,
+    pub max_speed:       f32,
+    pub max_temperature: f32,
+    pub bounces:         u32,
+    pub skips:           u32,
+    pub flips:           u32,
+    pub shots:           u32,
+    pub vaults:          u32,
+    pub burrowed:        f32,
+    pub rocks_cut:       u32,
+    /// Speed at first touching the ground or sea
+    pub landing_speed:   Option<f32>,
+    /// Rotation since the last counted flip, radians
+    pub flip_angle:      f32,
+    pub breakage:        Option<Breakage>,
+    pub outcome:         Option<Outcome>,
 }
 
 impl Stats {
     pub fn as_vec(&self) -> Vec<Stat> {
-        vec![
+        let mut stats = vec![
             Stat {field: "Time".to_string(), value: self.time, unit: "s".to_string()},
             Stat {field: "Distance".to_string(), value: self.distance, unit: "m".to_string()},
             Stat {field: "Max Altitude".to_string(), value: self.max_altitude, unit: "m".to_string()},
             Stat {field: "Max Speed".to_string(), value: self.max_speed, unit: "m/s".to_string()},
-        ]
+            Stat {field: "Max Temperature".to_string(), value: self.max_temperature, unit: "K".to_string()},
+            Stat {field: "Bounces".to_string(), value: self.bounces as f32, unit: "".to_string()},
+            Stat {field: "Skips".to_string(), value: self.skips as f32, unit: "".to_string()},
+            Stat {field: "Flips".to_string(), value: self.flips as f32, unit: "".to_string()},
+            Stat {field: "Shots Fired".to_string(), value: self.shots as f32, unit: "".to_string()},
+            Stat {field: "Landing Speed".to_string(), value: self.landing_speed.unwrap_or_default(), unit: "m/s".to_string()},
+        ];
+        // Melee lines only show up once the gear did something
+        if self.vaults > 0 {
+            stats.push(Stat {field: "Halberd Vaults".to_string(), value: self.vaults as f32, unit: "".to_string()});
+        }
+        if self.burrowed > 0.0 {
+            stats.push(Stat {field: "Screw Burrowed".to_string(), value: self.burrowed, unit: "m".to_string()});
+        }
+        if self.rocks_cut > 0 {
+            stats.push(Stat {field: "Rocks Sliced".to_string(), value: self.rocks_cut as f32, unit: "".to_string()});
+        }
+        stats
+    }
+    /// Counts a flip for every full turn in one direction
+    pub fn record_turn(&mut self, angle: f32) {
+        self.flip_angle += angle;
+        if self.flip_angle.abs() >= consts::TAU {
+            self.flips += 1;
+            self.flip_angle -= consts::TAU.copysign(self.flip_angle);
+        }
     }
+
+    /// Research earned for the flight, a broken engine earns nothing
     pub fn crunch(&self) -> u32 {
-        ((self.distance * 0.1) + (self.max_altitude * 0.3) + (self.max_speed * 0.6)) as u32
+        if self.breakage.is_some() {
+            return 0;
+        }
+        let gentle = self
+            .landing_speed
+            .map_or(0.0, |speed| (1.0 - speed / GENTLE_SPEED).max(0.0));
+        ((self.distance * 0.1)
+            + (self.max_altitude * 0.3)
+            + (self.max_speed * 0.6)
+            + (gentle * GENTLE_BONUS)) as u32
+    }
+}
+
+#[cfg(test)]
+mod stats_test {
+    use super::*;
+
+    #[test]
+    fn gentle_landing() {
+        let flight = Stats {
+            distance: 1000.0,
+            max_speed: 50.0,
+            ..Default::default()
+        };
+        let crash = Stats {
+            landing_speed: Some(40.0),
+            ..flight
+        };
+        let soft = Stats {
+            landing_speed: Some(1.0),
+            ..flight
+        };
+        assert_eq!(crash.crunch(), flight.crunch());
+        assert_eq!(soft.crunch(), flight.crunch() + 80);
+    }
+
+    #[test]
+    fn broken_pays_nothing() {
+        let broken = Stats {
+            distance: 1000.0,
+            max_speed: 50.0,
+            breakage: Some(Breakage::ArmSnapped),
+            ..Default::default()
+        };
+        assert_eq!(broken.crunch(), 0);
     }
-}
\ No newline at end of file
+}
@@ -17,7 +17,7 @@ struct Tech {
     pub icon: Option<String>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TechTree {
     pub names:    Vec<Box<str>>,
     pub costs:    Vec<u32>,
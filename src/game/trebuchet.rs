@@ -2,11 +2,23 @@
 use core::{f32::consts, fmt};
 use macroquad::math::*;
 use crate::utils::*;
+use super::launcher::{Breakage, Launcher, GRAVITY};
 
-const GRAVITY: f32 = 9.81;
+pub mod blueprint;
+pub mod optimizer;
+
+// Bail out of headless launches that never reach release
+const MAX_LAUNCH_TIME: f32 = 10.0;
+// Arms are hollow square beams, side and wall scale with arm length
+const ARM_SIDE: f32 = 1.0 / 8.0;
+const ARM_WALL: f32 = 1.0 / 20.0;
+// Below this speed wheels are held by static friction
+const WHEEL_STICK: f32 = 1.0e-3;
+// Arm angle where it slams into the stop bar, lying forward, and the sling lets go whatever the setting
+const ARM_STOP: f32 = -consts::FRAC_PI_2;
 use TrebuchetMaterial as TM;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub enum TrebuchetMaterial {
     #[default]
     Cardboard,
@@ -22,13 +34,92 @@ impl fmt::Display for TrebuchetMaterial {
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl TrebuchetMaterial {
+    /// kg/m^3
+    pub fn density(&self) -> f32 {
+        match self {
+            TM::Cardboard => 120.0,
+            TM::Wood1 => 450.0,
+            TM::Wood2 => 750.0,
+            TM::Steel => 7850.0,
+            TM::Space => 1600.0,
+        }
+    }
+
+    /// Bending stress the arm takes before snapping, Pa
+    pub fn strength(&self) -> f32 {
+        match self {
+            TM::Cardboard => 1.5e6,
+            TM::Wood1 => 40.0e6,
+            TM::Wood2 => 90.0e6,
+            TM::Steel => 400.0e6,
+            TM::Space => 3.0e9,
+        }
+    }
+
+    /// Sling tension before the rope gives, N
+    pub fn sling_strength(&self) -> f32 {
+        match self {
+            TM::Cardboard => 400.0,
+            TM::Wood1 => 3_000.0,
+            TM::Wood2 => 8_000.0,
+            TM::Steel => 50_000.0,
+            TM::Space => 1.0e6,
+        }
+    }
+
+    /// Longest arm the workshop can put together out of this material
+    pub fn max_arm(&self) -> Meters {
+        match self {
+            TM::Cardboard => 3.0,
+            TM::Wood1 => 6.0,
+            TM::Wood2 => 10.0,
+            TM::Steel => 20.0,
+            TM::Space => 40.0,
+        }
+    }
+
+    /// Heaviest counterweight the workshop can hang off this material
+    pub fn max_weight(&self) -> Kilograms {
+        match self {
+            TM::Cardboard => 100.0,
+            TM::Wood1 => 500.0,
+            TM::Wood2 => 2_000.0,
+            TM::Steel => 10_000.0,
+            TM::Space => 50_000.0,
+        }
+    }
+
+    /// Rocks to rebuild the trebuchet out of this material
+    pub fn cost(&self) -> u32 {
+        match self {
+            TM::Cardboard => 0,
+            TM::Wood1 => 20,
+            TM::Wood2 => 60,
+            TM::Steel => 250,
+            TM::Space => 1000,
+        }
+    }
+
+    pub fn next(&self) -> Option<TrebuchetMaterial> {
+        match self {
+            TM::Cardboard => Some(TM::Wood1),
+            TM::Wood1 => Some(TM::Wood2),
+            TM::Wood2 => Some(TM::Steel),
+            TM::Steel => Some(TM::Space),
+            TM::Space => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum TrebuchetState {
     Stage1,
     Stage2,
     Stage3,
 }
 
+#[derive(Clone, Copy)]
 pub struct TrebuchetArm {
     long_length:  f32,
     short_length: f32,
@@ -41,7 +132,8 @@ pub struct TrebuchetArm {
 }
 
 impl TrebuchetArm {
-    pub fn new(long_length: f32, short_length: f32, mass: f32, material: TM) -> TrebuchetArm {
+    pub fn new(long_length: f32, short_length: f32, material: TM) -> TrebuchetArm {
+        let mass = material.density() * (long_length + short_length) * Self::section_area(long_length + short_length);
         Self {
             long_length,
             short_length,
@@ -58,11 +150,27 @@ impl TrebuchetArm {
         self.long_length + self.short_length
     }
 
+    fn section_area(total_length: f32) -> f32 {
+        let side = total_length * ARM_SIDE;
+        side.powi(2) - (side * (1.0 - 2.0 * ARM_WALL)).powi(2)
+    }
+
+    /// Section modulus of the hollow beam, bending stress is moment over this
+    pub fn section_modulus(&self) -> f32 {
+        let side = self.total_length() * ARM_SIDE;
+        (side.powi(4) - (side * (1.0 - 2.0 * ARM_WALL)).powi(4)) / (6.0 * side)
+    }
+
+    pub fn material(&self) -> TM {
+        self.material
+    }
+
     pub fn texture(&self) -> String {
         format!("{}_arm", self.material.to_string().to_lowercase())
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct TrebuchetWeight {
     pub length:   f32,
     mass:     f32,
@@ -70,6 +178,7 @@ pub struct TrebuchetWeight {
     angle:    f32,
     velocity: f32,
     material: TM,
+    pub hinged: bool,
 }
 
 impl TrebuchetWeight {
@@ -81,18 +190,60 @@ impl TrebuchetWeight {
             angle:    0.0,
             velocity: 0.0,
             material,
+            hinged:   true,
+        }
+    }
+
+    /// Counterweight bolted in line with the short arm instead of swinging
+    pub fn fixed(length: f32, mass: f32, material: TM) -> TrebuchetWeight {
+        TrebuchetWeight {
+            hinged: false,
+            ..TrebuchetWeight::new(length, mass, material)
         }
     }
 
     pub fn texture(&self) -> String {
         format!("{}_weight", self.material.to_string().to_lowercase())
     }
+
+    /// Lengths to the weight's hinge and from it to the weight,
+    /// a fixed weight hangs rigidly off the end of the short arm so it all counts as arm
+    fn lever(&self, short_length: f32) -> (f32, f32) {
+        match self.hinged {
+            true => (short_length, self.length),
+            false => (short_length + self.length, 0.0),
+        }
+    }
 }
 
+/// What lets the sling go
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum ReleaseMode {
+    /// Ring slips off the pin once the projectile heads at or below the release angle
+    #[default]
+    Pin,
+    /// Trigger fires once the arm swings the release angle past vertical
+    Arm,
+}
+
+impl ReleaseMode {
+    /// Release angles the mechanism can be set to, degrees
+    pub fn angle_range(&self) -> std::ops::Range<f32> {
+        match self {
+            ReleaseMode::Pin => 1.0..90.0,
+            ReleaseMode::Arm => 1.0..30.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct TrebuchetSling {
     length:   f32,
     angle:    f32,
     velocity: f32,
+
+    pub release_angle: f32,
+    pub release_mode:  ReleaseMode,
 }
 
 impl TrebuchetSling {
@@ -101,6 +252,8 @@ impl TrebuchetSling {
             length,
             angle:    0.0,
             velocity: 0.0,
+            release_angle: consts::FRAC_PI_4,
+            release_mode:  ReleaseMode::Pin,
         }
     }
 
@@ -112,6 +265,39 @@ impl TrebuchetSling {
     }
 }
 
+/// Wheels under the base, letting it roll back and forth while firing
+#[derive(Clone, Copy)]
+pub struct TrebuchetWheels {
+    pub offset:     Meters,
+    velocity:       f32,
+    /// Rolling resistance of the ground beneath the base
+    pub resistance: f32,
+    /// Downrange direction along the surface the base rolls on
+    pub tangent:    Vec2,
+}
+
+impl Default for TrebuchetWheels {
+    fn default() -> Self {
+        TrebuchetWheels {
+            offset:     0.0,
+            velocity:   0.0,
+            resistance: 0.0,
+            tangent:    Vec2::X,
+        }
+    }
+}
+
+/// Pose of the moving parts at one physics tick, for replays
+#[derive(Debug, Clone, Copy)]
+pub struct TrebuchetFrame {
+    pub arm:        f32,
+    pub weight:     f32,
+    pub sling:      f32,
+    pub base:       Meters,
+    /// Follows the sling until release, then the plushie in flight
+    pub projectile: I64Vec2,
+}
+
 #[derive(Default)]
 pub struct TrebuchetBuilder {
     position: I64Vec2,
@@ -122,6 +308,9 @@ pub struct TrebuchetBuilder {
     arm:    Option<TrebuchetArm>,
     weight: Option<TrebuchetWeight>,
     sling:  Option<TrebuchetSling>,
+    wheels: Option<TrebuchetWheels>,
+
+    release: Option<(ReleaseMode, f32)>,
 }
 
 #[allow(dead_code)]
@@ -147,29 +336,56 @@ impl TrebuchetBuilder {
         self
     }
 
+    pub fn release(mut self, mode: ReleaseMode, angle: f32) -> Self {
+        self.release = Some((mode, angle));
+        self
+    }
+
+    pub fn wheels(mut self, resistance: f32) -> Self {
+        self.wheels = Some(TrebuchetWheels { resistance, ..Default::default() });
+        self
+    }
+
+    pub fn projectile(mut self, mass: f32) -> Self {
+        self.m_proj = Some(mass);
+        self
+    }
+
     pub fn center(mut self, center: f32) -> Self {
         if let Some(arm) = self.arm.as_mut() { arm.center = center }
         self
     }
 
     pub fn build(self) -> Trebuchet {
+        let mut sling = self.sling.unwrap_or(TrebuchetSling::new(1.6));
+        if let Some((mode, angle)) = self.release {
+            sling.release_mode = mode;
+            sling.release_angle = angle;
+        }
         Trebuchet {
             position: self.position,
             height: self.height.unwrap_or(1.0),
             material: self.material.unwrap_or(TM::Cardboard),
             // height: self.height.unwrap_or(5.6),
-            m_proj: 0.3,
-            arm: self.arm.unwrap_or(TrebuchetArm::new(1.6, 0.4, 0.25, TM::Cardboard)),
+            m_proj: self.m_proj.unwrap_or(0.3),
+            arm: self.arm.unwrap_or(TrebuchetArm::new(1.6, 0.4, TM::Cardboard)),
             weight: self.weight.unwrap_or(TrebuchetWeight::new(0.5, 50.0, TM::Cardboard)),
-            sling: self.sling.unwrap_or(TrebuchetSling::new(1.6)),
+            sling,
+            wheels: self.wheels,
             // arm: self.arm.unwrap_or(TrebuchetArm::new(8.0, 2.0, 12.0, TM::Cardboard)),
             // weight: self.weight.unwrap_or(TrebuchetWeight::new(2.0, 100.0, TM::Cardboard)),
             // sling: self.sling.unwrap_or(TrebuchetSling::new(8.0, TM::Cardboard)),
             state: TrebuchetState::Stage1,
+            energy_start: 0.0,
+            energy_drift: 0.0,
+            arm_stress:    0.0,
+            sling_tension: 0.0,
+            breakage:      None,
         }
     }
 } 
 
+#[derive(Clone)]
 pub struct Trebuchet {
     pub position: I64Vec2,
     pub height:   f32,
@@ -179,8 +395,16 @@ pub struct Trebuchet {
     pub arm:    TrebuchetArm,
     pub weight: TrebuchetWeight,
     pub sling:  TrebuchetSling,
+    pub wheels: Option<TrebuchetWheels>,
 
     state:    TrebuchetState,
+
+    energy_start: f32,
+    energy_drift: f32,
+
+    pub arm_stress:    f32,
+    pub sling_tension: f32,
+    breakage:          Option<Breakage>,
 }
 
 impl Trebuchet {
@@ -192,6 +416,40 @@ impl Trebuchet {
         format!("{}_base", self.material.to_string().to_lowercase())
     }
 
+    /// Two legs of the same beam as the arm
+    pub fn base_mass(&self) -> f32 {
+        let leg = self.height * 1.25;
+        2.0 * leg * self.material.density() * TrebuchetArm::section_area(self.arm.total_length())
+    }
+
+    /// Puts the base on wheels rolling against `resistance`, or back on the ground with `None`
+    pub fn set_wheels(&mut self, resistance: Option<f32>) {
+        self.wheels = resistance.map(|resistance| TrebuchetWheels { resistance, ..Default::default() });
+        self.reset();
+    }
+
+    /// Ground under the wheels, kept up to date as the base rolls onto other terrain
+    pub fn set_ground(&mut self, resistance: f32, tangent: Vec2) {
+        if let Some(wheels) = self.wheels.as_mut() {
+            wheels.resistance = resistance;
+            wheels.tangent = tangent;
+        }
+    }
+
+    pub fn breakage(&self) -> Option<Breakage> {
+        self.breakage
+    }
+
+    /// Rebuilds the frame, arm and weight box out of another material
+    pub fn set_material(&mut self, material: TM) {
+        let center = self.arm.center;
+        self.material = material;
+        self.arm = TrebuchetArm::new(self.arm.long_length, self.arm.short_length, material);
+        self.arm.center = center;
+        self.weight.material = material;
+        self.reset();
+    }
+
     pub fn armsling_point(&self) -> Vec2 {
         Vec2::from_angle(self.arm.angle).rotate(Vec2::Y * self.arm.long_length)
     }
@@ -210,66 +468,376 @@ impl Trebuchet {
             + self.armweight_point()
     }
 
+    pub fn arm_center_point(&self) -> Vec2 {
+        Vec2::from_angle(self.arm.angle).rotate(Vec2::Y * self.arm.center)
+    }
+
+    /// Where the wheels have rolled the base to along the surface, relative to `position`
+    pub fn base_offset(&self) -> Vec2 {
+        self.wheels.map_or(Vec2::ZERO, |wheels| wheels.tangent * wheels.offset)
+    }
+
+    pub fn base_point(&self) -> I64Vec2 {
+        to_i64coords(self.base_offset()) + self.position
+    }
+
+    pub fn v_base(&self) -> Vec2 {
+        self.wheels.map_or(Vec2::ZERO, |wheels| wheels.tangent * wheels.velocity)
+    }
+
     pub fn projectile_position (&self) -> I64Vec2 {
-        to_i64coords(self.sling_point() + Vec2::Y * self.height) + self.position
+        to_i64coords(self.sling_point() + Vec2::Y * self.height + self.base_offset()) + self.position
     }
 
     pub fn v_projectile(&self) -> Vec2 {
         Vec2::from_angle(self.arm.angle + self.sling.angle).rotate(Vec2::NEG_X * self.sling.length) 
             * (self.arm.velocity + self.sling.velocity)
             + self.armsling_point().perp() * self.arm.velocity
+            + self.v_base()
     }
 
     pub fn w_projectile(&self) -> f32 {
         self.arm.velocity + self.sling.velocity
     }
 
+    pub fn v_weight(&self) -> Vec2 {
+        (self.weight_point() - self.armweight_point()).perp() * (self.arm.velocity + self.weight.velocity)
+            + self.armweight_point().perp() * self.arm.velocity
+            + self.v_base()
+    }
+
+    /// Total kinetic and potential energy of base, arm, weight and projectile, zeroed at the pivot
+    pub fn energy(&self) -> f32 {
+        let v_arm = self.arm_center_point().perp() * self.arm.velocity + self.v_base();
+        let arm_kinetic = 0.5 * self.arm.inertia * self.arm.velocity.powi(2)
+            + 0.5 * self.arm.mass * v_arm.length_squared();
+        let base_kinetic = 0.5 * self.base_mass() * self.v_base().length_squared();
+        let weight_kinetic = 0.5 * self.weight.mass * self.v_weight().length_squared()
+            + 0.5 * self.weight.inertia * (self.arm.velocity + self.weight.velocity).powi(2);
+        // Projectile is no longer part of the system once released
+        let m_proj = match self.state {
+            TrebuchetState::Stage3 => 0.0,
+            _ => self.m_proj,
+        };
+        let proj_kinetic = 0.5 * m_proj * self.v_projectile().length_squared();
+
+        let potential = GRAVITY * (self.arm.mass * self.arm_center_point().y
+            + self.weight.mass * self.weight_point().y
+            + m_proj * self.sling_point().y);
+
+        base_kinetic + arm_kinetic + weight_kinetic + proj_kinetic + potential
+    }
+
+    /// Largest deviation from the starting energy seen so far
+    pub fn energy_drift(&self) -> f32 {
+        self.energy_drift
+    }
+
+    pub fn frame(&self, projectile: I64Vec2) -> TrebuchetFrame {
+        TrebuchetFrame {
+            arm: self.arm.angle,
+            weight: self.weight.angle,
+            sling: self.sling.angle,
+            base: self.wheels.map_or(0.0, |wheels| wheels.offset),
+            projectile,
+        }
+    }
+
+    /// Moves the parts to a recorded frame without touching the simulation state
+    pub fn pose(&mut self, frame: &TrebuchetFrame) {
+        self.arm.angle = frame.arm;
+        self.weight.angle = frame.weight;
+        self.sling.angle = frame.sling;
+        if let Some(wheels) = self.wheels.as_mut() {
+            wheels.offset = frame.base;
+        }
+    }
+
     pub fn reset(&mut self) {
         let common_triangle  = self.height / self.arm.long_length;
         self.arm.velocity    = 0.0;
         self.weight.velocity = 0.0;
         self.sling.velocity  = 0.0;
         self.arm.angle       = consts::PI - common_triangle.acos();
-        self.weight.angle    = common_triangle.acos() - consts::PI;
+        self.weight.angle    = match self.weight.hinged {
+            true => common_triangle.acos() - consts::PI,
+            false => 0.0,
+        };
         self.sling.angle     = consts::PI - common_triangle.asin();
+        if let Some(wheels) = self.wheels.as_mut() {
+            wheels.offset    = 0.0;
+            wheels.velocity  = 0.0;
+        }
         self.state           = TrebuchetState::Stage1;
-        self.m_proj          = 0.3;
+        self.energy_start    = self.energy();
+        self.energy_drift    = 0.0;
+        self.arm_stress      = 0.0;
+        self.sling_tension   = 0.0;
+        self.breakage        = None;
     }
 
     pub fn run(&mut self, dt: f32) -> bool {
-        let mat = mat3a(
-            vec3a(self.arm.angle, self.weight.angle, self.sling.angle),
-            vec3a(self.arm.velocity, self.weight.velocity, self.sling.velocity),
-            Vec3A::ZERO
+        // Broken trebuchets stay put with the projectile let go
+        if self.breakage.is_some() {
+            return true;
+        }
+
+        let v_proj = self.v_projectile();
+        let v_weight = self.v_weight();
+        if self.wheels.is_some() {
+            self.roll(dt);
+        } else {
+            let mat = mat3a(
+                vec3a(self.arm.angle, self.weight.angle, self.sling.angle),
+                vec3a(self.arm.velocity, self.weight.velocity, self.sling.velocity),
+                Vec3A::ZERO
+            );
+
+            let stage: Box<dyn Fn(f32, Mat3A) -> Mat3A> = match self.state {
+                TrebuchetState::Stage1 => {
+                    let Mat3A{x_axis: _, y_axis: Vec3A{x: aw_prime, ..}, ..} = self.stage_1(dt, mat);
+                    if self.ground_force(aw_prime) <= 0.0 {
+                        self.state = TrebuchetState::Stage2;
+                    }
+                    Box::new(|dt: f32, x: Mat3A| self.stage_1(dt, x))
+                }
+
+                TrebuchetState::Stage2 => {
+                    if self.releasing() {
+                        self.state = TrebuchetState::Stage3;
+                        // Projectile energy leaves with it
+                        self.energy_start = self.energy();
+                        Box::new(|dt: f32, x: Mat3A| self.stage_3(dt, x))
+                    } else {
+                        Box::new(|dt: f32, x: Mat3A| self.stage_2(dt, x))
+                    }
+                }
+
+                TrebuchetState::Stage3 => {
+                    Box::new(|dt: f32, x: Mat3A| self.stage_3(dt, x))
+                }
+            };
+        
+            let rk4_results = rk5(mat, dt, stage);
+            Vec3A {x: self.arm.angle,    y: self.weight.angle,    z: self.sling.angle  } = rk4_results.x_axis;
+            Vec3A {x: self.arm.velocity, y: self.weight.velocity, z:self.sling.velocity} = rk4_results.y_axis;
+        }
+
+        self.energy_drift = self.energy_drift.max((self.energy() - self.energy_start).abs());
+
+        self.check_loads(v_proj, v_weight, dt);
+
+        self.state == TrebuchetState::Stage3 || self.breakage.is_some()
+    }
+
+    /// Bending stress at the pivot and sling tension from the change in velocity over the tick
+    fn check_loads(&mut self, v_proj: Vec2, v_weight: Vec2, dt: f32) {
+        let gravity = Vec2::NEG_Y * GRAVITY;
+
+        let weight_force = self.weight.mass * ((self.v_weight() - v_weight) / dt - gravity);
+        let weight_arm = match self.weight.hinged {
+            true => self.armweight_point(),
+            false => self.weight_point(),
+        };
+        let weight_moment = weight_arm.perp_dot(weight_force);
+
+        let sling_force = match self.state {
+            TrebuchetState::Stage3 => Vec2::ZERO,
+            _ => {
+                // Sling only pulls, any push along it is the ground
+                let along = (self.armsling_point() - self.sling_point()).normalize_or_zero();
+                let force = self.m_proj * ((self.v_projectile() - v_proj) / dt - gravity);
+                along * force.dot(along).max(0.0)
+            }
+        };
+        let sling_moment = self.armsling_point().perp_dot(sling_force);
+
+        self.sling_tension = sling_force.length();
+        self.arm_stress = weight_moment.abs().max(sling_moment.abs()) / self.arm.section_modulus();
+
+        if self.arm_stress > self.arm.material.strength() {
+            self.breakage = Some(Breakage::ArmSnapped);
+        } else if self.sling_tension > self.material.sling_strength() {
+            self.breakage = Some(Breakage::SlingSnapped);
+        }
+    }
+
+    /// Same stages as `run` with the base rolling on its wheels as a fourth coordinate
+    fn roll(&mut self, dt: f32) {
+        let wheels = self.wheels.unwrap_or_default();
+        let mat = Mat4::from_cols(
+            vec4(self.arm.angle, self.weight.angle, self.sling.angle, wheels.offset),
+            vec4(self.arm.velocity, self.weight.velocity, self.sling.velocity, wheels.velocity),
+            Vec4::ZERO,
+            Vec4::ZERO,
         );
 
-        let stage: Box<dyn Fn(f32, Mat3A) -> Mat3A> = match self.state {
+        match self.state {
             TrebuchetState::Stage1 => {
-                let Mat3A{x_axis: _, y_axis: Vec3A{x: aw_prime, ..}, ..} = self.stage_1(dt, mat);
-                if self.ground_force(aw_prime) <= 0.0 {
+                if self.wheeled_accel(mat).1 <= 0.0 {
                     self.state = TrebuchetState::Stage2;
                 }
-                Box::new(|dt: f32, x: Mat3A| self.stage_1(dt, x))
             }
-
             TrebuchetState::Stage2 => {
-                if to_angle(self.v_projectile()) <= consts::FRAC_PI_4 {
-                    self.m_proj = 0.01;
+                if self.releasing() {
                     self.state = TrebuchetState::Stage3;
+                    self.energy_start = self.energy();
                 }
-                Box::new(|dt: f32, x: Mat3A| self.stage_2(dt, x))
             }
+            TrebuchetState::Stage3 => {}
+        }
 
-            TrebuchetState::Stage3 => {
-                Box::new(|dt: f32, x: Mat3A| self.stage_2(dt, x))
+        let results = rk5(mat, dt, |_t: f32, x: Mat4| {
+            let (accelerations, _) = self.wheeled_accel(x);
+            let velocities = match self.state {
+                TrebuchetState::Stage3 => x.y_axis * vec4(1.0, 1.0, 0.0, 1.0),
+                _ => x.y_axis,
+            };
+            Mat4::from_cols(velocities, accelerations, Vec4::ZERO, Vec4::ZERO)
+        });
+
+        let wheels = self.wheels.get_or_insert_with(Default::default);
+        Vec4 {x: self.arm.angle,    y: self.weight.angle,    z: self.sling.angle,    w: wheels.offset  } = results.x_axis;
+        Vec4 {x: self.arm.velocity, y: self.weight.velocity, z: self.sling.velocity, w: wheels.velocity} = results.y_axis;
+    }
+
+    /// Accelerations of arm, weight, sling and base plus the ground force on the projectile,
+    /// with rolling resistance holding the base until the recoil overcomes it
+    fn wheeled_accel(&self, mat: Mat4) -> (Vec4, f32) {
+        let resistance = self.wheels.map_or(0.0, |wheels| wheels.resistance);
+        let m_proj = match self.state {
+            TrebuchetState::Stage3 => 0.0,
+            _ => self.m_proj,
+        };
+        let m_total = self.base_mass() + self.arm.mass + self.weight.mass + m_proj;
+        let limit = resistance * m_total * GRAVITY;
+
+        let velocity = mat.y_axis.w;
+        if velocity.abs() > WHEEL_STICK {
+            let (accel, ground, _) = self.wheeled_solve(mat, Some(-limit * velocity.signum()));
+            return (accel, ground);
+        }
+
+        let (accel, ground, holding) = self.wheeled_solve(mat, None);
+        match holding.abs() <= limit {
+            true => (accel, ground),
+            false => {
+                let (accel, ground, _) = self.wheeled_solve(mat, Some(limit * holding.signum()));
+                (accel, ground)
             }
+        }
+    }
+
+    /// Solves the coupled equations with a given horizontal force on the base,
+    /// or with the base held in place when `None`, also returning the force needed to hold it
+    fn wheeled_solve(&self, mat: Mat4, base_force: Option<f32>) -> (Vec4, f32, f32) {
+        let mp = match self.state {
+            TrebuchetState::Stage3 => 0.0,
+            _ => self.m_proj,
         };
-        
-        let rk4_results = rk5(mat, dt, stage);
-        Vec3A {x: self.arm.angle,    y: self.weight.angle,    z: self.sling.angle  } = rk4_results.x_axis;
-        Vec3A {x: self.arm.velocity, y: self.weight.velocity, z:self.sling.velocity} = rk4_results.y_axis;
+        let TrebuchetArm { 
+            long_length: lal, 
+            short_length: las, 
+            center: cga, 
+            mass: ma, 
+            inertia: ia, 
+            .. 
+        } = self.arm;
+        let TrebuchetWeight { 
+            mass: mw, 
+            inertia: iw, 
+            .. 
+        } = self.weight;
+        let (las, lw) = self.weight.lever(las);
+        let ls = self.sling.length;
+        let mb = self.base_mass() + ma + mw + mp;
+        let Vec4 {x: aq, y: wq, z: sq, ..} = mat.x_axis;
+        let Vec4 {x: aw, y: ww, z: sw, ..} = mat.y_axis;
+
+        // Base couples through the horizontal positions of each mass
+        let ca = -ma * cga * aq.cos() + mw * (las * aq.cos() + lw * (aq + wq).cos())
+            - mp * (lal * aq.cos() + ls * (aq + sq).cos());
+        let cw = mw * lw * (aq + wq).cos();
+        let cs = -mp * ls * (aq + sq).cos();
+        #[rustfmt::skip]
+        let centripetal = ma * cga * aq.sin() * aw.powi(2) - mw * (las * aq.sin() * aw.powi(2) 
+            + lw * (aq + wq).sin() * (aw + ww).powi(2)) + mp * (lal * aq.sin() * aw.powi(2) 
+            + ls * (aq + sq).sin() * (aw + sw).powi(2));
+
+        #[rustfmt::skip]
+        let m11 = ia + iw + ma * cga.powi(2) + mp * (lal.powi(2) + ls.powi(2) + 2.0 * lal * ls 
+            * sq.cos()) + mw * (las.powi(2) + lw.powi(2) + 2.0 * las * lw * wq.cos());
+        let m12 = iw + lw * mw * (lw + las * wq.cos());
+        let m13 = ls * mp * (ls + lal * sq.cos());
+        let m22 = iw + mw * lw.powi(2);
+        let m33 = mp * ls.powi(2);
+
+        #[rustfmt::skip]
+        let r = [
+            GRAVITY * cga * ma * aq.sin() + GRAVITY * mp * (lal * aq.sin() + ls * (aq + sq).sin()) 
+            - GRAVITY * mw * (las * aq.sin() + lw * (aq + wq).sin()) - lal * ls * mp * sq.sin() 
+            * (aw.powi(2) - (aw + sw).powi(2)) - las * lw * mw * wq.sin() * (aw.powi(2) 
+            - (aw + ww).powi(2)),
+            -lw * mw * (GRAVITY * (aq + wq).sin() + las * wq.sin() * aw.powi(2)),
+            ls * mp * (GRAVITY * (aq + sq).sin() - lal * sq.sin() * aw.powi(2)),
+            base_force.unwrap_or(0.0) - centripetal,
+        ];
+
+        // Last row is the projectile staying on the ground in Stage1, ground force as multiplier
+        let (ja, js) = (-lal * aq.sin() - ls * (aq + sq).sin(), -ls * (aq + sq).sin());
+        let mut a = [
+            [m11, m12, m13, ca, -ja],
+            [m12, m22, 0.0, cw, 0.0],
+            [m13, 0.0, m33, cs, -js],
+            [ca, cw, cs, mb, 0.0],
+            [ja, 0.0, js, 0.0, 0.0],
+        ];
+        let mut b = [
+            r[0],
+            r[1],
+            r[2],
+            r[3],
+            lal * aq.cos() * aw.powi(2) + ls * (aq + sq).cos() * (aw + sw).powi(2),
+        ];
+
+        let mut lock = |i: usize| {
+            (0..5).for_each(|j| {
+                a[i][j] = 0.0;
+                a[j][i] = 0.0;
+            });
+            a[i][i] = 1.0;
+            b[i] = 0.0;
+        };
+        if !self.weight.hinged {
+            lock(1);
+        }
+        if self.state == TrebuchetState::Stage3 {
+            lock(2);
+        }
+        if base_force.is_none() {
+            lock(3);
+        }
+        if self.state != TrebuchetState::Stage1 {
+            lock(4);
+        }
 
-        self.state == TrebuchetState::Stage3
+        let x = solve_linear(a, b);
+        let holding = ca * x[0] + cw * x[1] + cs * x[2] + centripetal;
+        (vec4(x[0], x[1], x[2], x[3]), x[4], holding)
+    }
+
+    fn releasing(&self) -> bool {
+        if self.arm.angle <= ARM_STOP {
+            return true;
+        }
+        match self.sling.release_mode {
+            ReleaseMode::Pin => {
+                // Signed so heading just below horizontal still counts as below the pin
+                let v_proj = self.v_projectile();
+                v_proj.x > 0.0 && v_proj.y.atan2(v_proj.x) <= self.sling.release_angle
+            }
+            ReleaseMode::Arm => -self.arm.angle >= self.sling.release_angle,
+        }
     }
 
     fn ground_force(&self, aw_prime: f32) -> f32 {
@@ -284,7 +852,25 @@ impl Trebuchet {
             / (self.arm.angle + self.sling.angle).sin())
     }
 
-    fn stage_1(&self, dt: f32, mat: Mat3A) -> Mat3A {
+    /// Freezes the weight angle of fixed counterweights
+    fn lock_weight2(&self, m: Mat2, r: Vec2) -> (Mat2, Vec2) {
+        match self.weight.hinged {
+            true => (m, r),
+            false => (Mat2::from_diagonal(vec2(m.x_axis.x, 1.0)), r.with_y(0.0)),
+        }
+    }
+
+    fn lock_weight3(&self, m: Mat3A, r: Vec3A) -> (Mat3A, Vec3A) {
+        match self.weight.hinged {
+            true => (m, r),
+            false => (
+                Mat3A::from_cols(m.x_axis.with_y(0.0), Vec3A::Y, m.z_axis.with_y(0.0)),
+                r.with_y(0.0),
+            ),
+        }
+    }
+
+    fn stage_1(&self, _t: f32, mat: Mat3A) -> Mat3A {
         let mp = self.m_proj;
         let TrebuchetArm { 
             long_length: lal, 
@@ -295,11 +881,11 @@ impl Trebuchet {
             .. 
         } = self.arm;
         let TrebuchetWeight { 
-            length: lw, 
             mass: mw, 
             inertia: iw, 
             .. 
         } = self.weight;
+        let (las, lw) = self.weight.lever(las);
         let ls = self.sling.length;
         let Vec3A {x: aq, y: wq, z: sq} = mat.x_axis;
         let Vec3A {x: aw, y: ww, z: sw} = mat.y_axis;
@@ -324,9 +910,10 @@ impl Trebuchet {
             / (aq + sq).sin() + lal * aq.cos() / (ls * (aq + sq).sin())) * aw.powi(2))) 
             / (aq + sq).sin() - GRAVITY * mw * (las * aq.sin() + lw * (aq + wq).sin()) 
             - las * lw * mw * wq.sin() * (aw.powi(2) - (aw + ww).powi(2)),
-            -lw * mw * (GRAVITY * (aq + wq) + las * wq.sin() * aw.powi(2))
+            -lw * mw * (GRAVITY * (aq + wq).sin() + las * wq.sin() * aw.powi(2))
         );
 
+        let (m, r) = self.lock_weight2(m, r);
         let arm_weight_accel = m.inverse().mul_vec2(r);
         #[rustfmt::skip]
         let sw_prime = -(aq + sq).cos() * sw * (sw + 2.0 * aw) / (aq + sq).sin() 
@@ -336,13 +923,13 @@ impl Trebuchet {
         let accelerations: Vec3A = arm_weight_accel.extend(sw_prime).into();
                 
         mat3a(
-            mat.y_axis + accelerations * dt,
+            mat.y_axis,
             accelerations,
             Vec3A::ZERO,
         )
     }
 
-    fn stage_2(&self, dt: f32, mat: Mat3A) -> Mat3A {
+    fn stage_2(&self, _t: f32, mat: Mat3A) -> Mat3A {
         let mp = self.m_proj;
         let TrebuchetArm { 
             long_length: lal, 
@@ -353,11 +940,11 @@ impl Trebuchet {
             .. 
         } = self.arm;
         let TrebuchetWeight { 
-            length: lw, 
             mass: mw, 
             inertia: iw, 
             .. 
         } = self.weight;
+        let (las, lw) = self.weight.lever(las);
         let ls = self.sling.length;
         let Vec3A {x: aq, y: wq, z: sq} = mat.x_axis;
         let Vec3A {x: aw, y: ww, z: sw} = mat.y_axis;
@@ -386,21 +973,182 @@ impl Trebuchet {
             ls * mp * (GRAVITY * (aq + sq).sin() - lal * sq.sin() * aw.powi(2)),
         );
 
+        let (m, r) = self.lock_weight3(m, r);
         let accelerations = m.inverse().mul_vec3a(r);
         
         mat3a(
-            mat.y_axis + accelerations * dt,
+            mat.y_axis,
+            accelerations,
+            Vec3A::ZERO
+        )
+    }
+
+
+    fn stage_3(&self, _t: f32, mat: Mat3A) -> Mat3A {
+        let TrebuchetArm { 
+            short_length: las, 
+            center: cga, 
+            mass: ma, 
+            inertia: ia, 
+            .. 
+        } = self.arm;
+        let TrebuchetWeight { 
+            mass: mw, 
+            inertia: iw, 
+            .. 
+        } = self.weight;
+        let (las, lw) = self.weight.lever(las);
+        let Vec3A {x: aq, y: wq, ..} = mat.x_axis;
+        let Vec3A {x: aw, y: ww, ..} = mat.y_axis;
+
+        // Stage 2 without the projectile, empty sling is carried along with the arm
+        #[rustfmt::skip]
+        let m = Mat2::from_cols_array(&[
+            ia + iw + ma * cga.powi(2) + mw * (las.powi(2) + lw.powi(2) + 2.0 * las * lw * wq.cos()),
+            iw + lw * mw * (lw + las * wq.cos()),
+            iw + lw * mw * (lw + las * wq.cos()),
+            iw + mw * lw.powi(2),
+        ]);
+
+        #[rustfmt::skip]
+        let r = vec2(
+            GRAVITY * cga * ma * aq.sin() - GRAVITY * mw * (las * aq.sin() + lw * (aq + wq).sin()) 
+            - las * lw * mw * wq.sin() * (aw.powi(2) - (aw + ww).powi(2)),
+            -lw * mw * (GRAVITY * (aq + wq).sin() + las * wq.sin() * aw.powi(2)),
+        );
+
+        let (m, r) = self.lock_weight2(m, r);
+        let accelerations: Vec3A = m.inverse().mul_vec2(r).extend(0.0).into();
+
+        mat3a(
+            mat.y_axis * vec3a(1.0, 1.0, 0.0),
             accelerations,
             Vec3A::ZERO
         )
     }
 }
 
+impl Launcher for Trebuchet {
+    fn advance(&mut self, dt: f32) -> bool {
+        self.run(dt)
+    }
+
+    fn released(&self) -> bool {
+        // Snapped parts let go of the plushie as much as the sling does
+        self.state == TrebuchetState::Stage3 || self.breakage.is_some()
+    }
+
+    fn reset(&mut self) {
+        Trebuchet::reset(self)
+    }
+
+    fn position(&self) -> I64Vec2 {
+        self.position
+    }
+
+    fn set_position(&mut self, position: I64Vec2) {
+        self.position = position;
+    }
+
+    fn projectile_position(&self) -> I64Vec2 {
+        Trebuchet::projectile_position(self)
+    }
+
+    fn projectile_velocity(&self) -> Vec2 {
+        self.v_projectile()
+    }
+
+    fn projectile_rotation(&self) -> f32 {
+        (self.sling_point() - self.armsling_point()).to_angle()
+    }
+
+    fn breakage(&self) -> Option<Breakage> {
+        self.breakage
+    }
+}
+
+/// Summary of a launch at the moment the projectile leaves the sling
+#[derive(Debug, Clone, Copy)]
+pub struct LaunchReport {
+    pub release_time:     f32,
+    pub release_velocity: Vec2,
+    pub release_angle:    f32,
+    pub release_position: Vec2,
+    pub peak_arm_speed:   f32,
+    pub efficiency:       f32,
+    /// Energy drift over the launch as a fraction of the energy the counterweight released
+    pub energy_drift:     f32,
+    pub peak_stress:      f32,
+    pub peak_tension:     f32,
+    pub breakage:         Option<Breakage>,
+}
+
+impl LaunchReport {
+    /// Flat ground range from the release point, ignoring the atmosphere
+    pub fn range(&self) -> Meters {
+        let Vec2 { x: vx, y: vy } = self.release_velocity;
+        let height = self.release_position.y.max(0.0);
+        vx * (vy + (vy.powi(2) + 2.0 * GRAVITY * height).sqrt()) / GRAVITY
+    }
+}
+
+/// Runs a trebuchet design from rest to release without touching the game loop.
+/// Returns `None` if the design never lets go of the projectile.
+pub fn launch_report(builder: TrebuchetBuilder, dt: f32) -> Option<LaunchReport> {
+    let mut trebuchet = builder.build();
+    trebuchet.reset();
+
+    let weight_start = trebuchet.weight_point().y;
+    let mut peak_arm_speed: f32 = 0.0;
+    let mut peak_stress: f32 = 0.0;
+    let mut peak_tension: f32 = 0.0;
+    let mut time = 0.0;
+
+    while time < MAX_LAUNCH_TIME {
+        // Release state is the one at the start of the tick that enters Stage3,
+        // same as what the player is handed in Physics::update
+        let velocity = trebuchet.v_projectile();
+        let position = trebuchet.sling_point() + Vec2::Y * trebuchet.height;
+        let weight_drop = weight_start - trebuchet.weight_point().y;
+
+        let released = trebuchet.run(dt);
+        peak_stress = peak_stress.max(trebuchet.arm_stress);
+        peak_tension = peak_tension.max(trebuchet.sling_tension);
+
+        if released {
+            let kinetic = 0.5 * trebuchet.m_proj * velocity.length_squared();
+            let potential = trebuchet.weight.mass * GRAVITY * weight_drop;
+            return Some(LaunchReport {
+                release_time: time,
+                release_velocity: velocity,
+                release_angle: velocity.y.atan2(velocity.x),
+                release_position: position,
+                peak_arm_speed,
+                efficiency: kinetic / potential,
+                energy_drift: trebuchet.energy_drift() / potential,
+                peak_stress,
+                peak_tension,
+                breakage: trebuchet.breakage(),
+            });
+        }
+
+        if !trebuchet.arm.velocity.is_finite() {
+            return None;
+        }
+        peak_arm_speed = peak_arm_speed.max(trebuchet.arm.velocity.abs());
+        time += dt;
+    }
+    None
+}
+
 #[cfg(test)]
 mod test {
-    use macroquad::math::Mat3;
+    use core::f32::consts;
+    use macroquad::math::{I64Vec2, Mat3, Vec2};
 
-    use crate::game::TrebuchetMaterial;
+    use super::{
+        launch_report, Breakage, ReleaseMode, Trebuchet, TrebuchetArm, TrebuchetMaterial, TrebuchetWeight, TM,
+    };
 
     #[test]
     fn mat_mul(){
@@ -422,4 +1170,98 @@ mod test {
     fn enum_str() {
         println!("{}", TrebuchetMaterial::Cardboard.to_string().to_lowercase())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn energy_conservation() {
+        let designs = [
+            Trebuchet::init(I64Vec2::ZERO),
+            Trebuchet::init(I64Vec2::ZERO)
+                .base(1.0, TM::Wood1)
+                .arm(TrebuchetArm::new(1.6, 0.4, TM::Wood1))
+                .weight(TrebuchetWeight::new(0.3, 200.0, TM::Wood1)),
+            Trebuchet::init(I64Vec2::ZERO).sling(0.8),
+            Trebuchet::init(I64Vec2::ZERO)
+                .base(2.0, TM::Wood1)
+                .arm(TrebuchetArm::new(4.0, 1.0, TM::Wood1))
+                .weight(TrebuchetWeight::new(0.8, 1000.0, TM::Wood1))
+                .sling(3.5),
+            Trebuchet::init(I64Vec2::ZERO)
+                .weight(TrebuchetWeight::fixed(0.3, 50.0, TM::Cardboard)),
+        ];
+        for builder in designs {
+            let report = launch_report(builder, 0.001).unwrap();
+            assert!(report.energy_drift < 1e-3, "{report:?}");
+        }
+    }
+
+    #[test]
+    fn material_limits() {
+        let heavy = |material| {
+            Trebuchet::init(I64Vec2::ZERO)
+                .base(1.0, material)
+                .arm(TrebuchetArm::new(1.6, 0.4, material))
+                .weight(TrebuchetWeight::new(0.3, 200.0, material))
+        };
+        let cardboard = launch_report(heavy(TM::Cardboard), 0.001).unwrap();
+        assert_eq!(cardboard.breakage, Some(Breakage::ArmSnapped));
+        let wood = launch_report(heavy(TM::Wood1), 0.001).unwrap();
+        assert_eq!(wood.breakage, None);
+
+        let default = launch_report(Trebuchet::init(I64Vec2::ZERO), 0.001).unwrap();
+        assert_eq!(default.breakage, None);
+    }
+
+    #[test]
+    fn release_modes() {
+        let early = Trebuchet::init(I64Vec2::ZERO).release(ReleaseMode::Pin, 1.2);
+        let late = Trebuchet::init(I64Vec2::ZERO).release(ReleaseMode::Pin, 0.4);
+        let early = launch_report(early, 0.001).unwrap();
+        let late = launch_report(late, 0.001).unwrap();
+        assert!(early.release_time < late.release_time);
+
+        let arm = Trebuchet::init(I64Vec2::ZERO).release(ReleaseMode::Arm, 0.1);
+        let arm = launch_report(arm, 0.001).unwrap();
+        assert!(arm.release_velocity.x > 0.0);
+        assert!(arm.energy_drift < 1e-3);
+
+        // Release settings survive a sling swapped in afterwards
+        let swapped = Trebuchet::init(I64Vec2::ZERO).release(ReleaseMode::Arm, 0.1).sling(1.2).build();
+        assert_eq!(swapped.sling.release_mode, ReleaseMode::Arm);
+
+        // Pin just above horizontal and one that can never be reached both let go by the stop
+        let flat = Trebuchet::init(I64Vec2::ZERO).release(ReleaseMode::Pin, 1.0_f32.to_radians());
+        assert!(launch_report(flat, 0.001).is_some());
+        let never = Trebuchet::init(I64Vec2::ZERO).release(ReleaseMode::Arm, consts::PI);
+        let never = launch_report(never, 0.001).unwrap();
+        assert!(never.release_velocity.length() > 0.0);
+    }
+
+    #[test]
+    fn wheeled_recoil() {
+        let rolling = launch_report(Trebuchet::init(I64Vec2::ZERO).wheels(0.0), 0.001).unwrap();
+        assert!(rolling.energy_drift < 1e-3, "{rolling:?}");
+
+        let fixed = launch_report(Trebuchet::init(I64Vec2::ZERO), 0.001).unwrap();
+        let stuck = launch_report(Trebuchet::init(I64Vec2::ZERO).wheels(100.0), 0.001).unwrap();
+        assert!((stuck.release_velocity - fixed.release_velocity).length() < 1e-2);
+
+        let mut trebuchet = Trebuchet::init(I64Vec2::ZERO).wheels(0.05).build();
+        trebuchet.reset();
+        while !trebuchet.run(0.001) {}
+        assert!(trebuchet.base_offset().x != 0.0);
+
+        // Rolls along a sloped surface instead of straight across
+        let slope = Vec2::from_angle(0.2);
+        trebuchet.set_ground(0.05, slope);
+        assert!(trebuchet.base_offset().normalize().dot(slope).abs() > 0.999);
+    }
+
+    #[test]
+    fn default_launch() {
+        let report = launch_report(Trebuchet::init(I64Vec2::ZERO), 0.001).unwrap();
+        println!("{report:?}");
+        assert!(report.release_time > 0.0);
+        assert!(report.release_velocity.length() > 0.0);
+        assert!(report.efficiency > 0.0 && report.efficiency < 1.0);
+    }
+}
@@ -0,0 +1,319 @@
+use super::*;
+use crate::GameError;
+use macroquad::file::load_string;
+use nanoserde::{DeJson, SerJson};
+
+const STORAGE_PREFIX: &str = "blueprint:";
+
+/// Anything in a blueprint that would not build a working trebuchet
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlueprintError {
+    UnknownMaterial(String),
+    UnknownRelease(String),
+    /// Field that is not a positive finite number
+    NotPositive(&'static str),
+    /// Long arm has to reach past the base for the sling to start on the ground
+    ArmTooShort,
+    /// Long and short arm together past what the material allows
+    ArmTooLong(f32),
+    /// Counterweight past what the material allows
+    TooHeavy(f32),
+    ReleaseAngle(f32),
+    NotStored(String),
+    /// Tech the design needs that has not been researched
+    Locked(String),
+    /// Rocks needed to rebuild in the blueprint's material
+    Unaffordable(u32),
+}
+
+impl fmt::Display for BlueprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlueprintError::UnknownMaterial(name) => write!(f, "Unknown material {name}"),
+            BlueprintError::UnknownRelease(name) => write!(f, "Unknown release {name}"),
+            BlueprintError::NotPositive(field) => write!(f, "{field} must be positive"),
+            BlueprintError::ArmTooShort => write!(f, "Long arm does not reach the ground"),
+            BlueprintError::ArmTooLong(max) => write!(f, "Arm is longer than the material's {max}m"),
+            BlueprintError::TooHeavy(max) => write!(f, "Weight is heavier than the material's {max}kg"),
+            BlueprintError::ReleaseAngle(angle) => write!(f, "Release angle {angle} out of range"),
+            BlueprintError::NotStored(name) => write!(f, "No blueprint named {name}"),
+            BlueprintError::Locked(tech) => write!(f, "Needs {tech} researched"),
+            BlueprintError::Unaffordable(cost) => write!(f, "Needs {cost} rocks to build"),
+        }
+    }
+}
+
+#[derive(DeJson, SerJson, Debug, Clone, PartialEq)]
+pub struct ArmBlueprint {
+    pub long:  f32,
+    pub short: f32,
+}
+
+#[derive(DeJson, SerJson, Debug, Clone, PartialEq)]
+pub struct WeightBlueprint {
+    pub length: f32,
+    pub mass:   f32,
+    pub hinged: bool,
+}
+
+#[derive(DeJson, SerJson, Debug, Clone, PartialEq)]
+pub struct SlingBlueprint {
+    pub length:  f32,
+    /// "Pin" or "Arm"
+    pub release: String,
+    /// Degrees, same as the release slider
+    pub angle:   f32,
+}
+
+/// Everything needed to rebuild a trebuchet, as stored in JSON
+#[derive(DeJson, SerJson, Debug, Clone, PartialEq)]
+pub struct Blueprint {
+    pub name:       String,
+    pub height:     f32,
+    pub material:   String,
+    pub arm:        ArmBlueprint,
+    pub weight:     WeightBlueprint,
+    pub sling:      SlingBlueprint,
+    /// Same as the builder's default plushie
+    #[nserde(default = "0.3")]
+    pub projectile: f32,
+    #[nserde(default)]
+    pub wheels:     bool,
+}
+
+impl Blueprint {
+    pub fn from_trebuchet(name: &str, trebuchet: &Trebuchet) -> Blueprint {
+        let (weight_length, hinged) = (trebuchet.weight.length, trebuchet.weight.hinged);
+        Blueprint {
+            name:     name.to_owned(),
+            height:   trebuchet.height,
+            material: trebuchet.material.to_string(),
+            arm:      ArmBlueprint {
+                long:  trebuchet.arm.long_length,
+                short: trebuchet.arm.short_length,
+            },
+            weight:   WeightBlueprint {
+                length: weight_length,
+                mass:   trebuchet.weight.mass,
+                hinged,
+            },
+            sling:    SlingBlueprint {
+                length:  trebuchet.sling.length,
+                release: format!("{:?}", trebuchet.sling.release_mode),
+                angle:   trebuchet.sling.release_angle.to_degrees(),
+            },
+            projectile: trebuchet.m_proj,
+            wheels:     trebuchet.wheels.is_some(),
+        }
+    }
+
+    /// Loads a blueprint shipped in `assets/`
+    pub async fn load(path: &str) -> Result<Blueprint, GameError> {
+        let json = load_string(path).await?;
+        Blueprint::from_json(&json)
+    }
+
+    /// Loads a blueprint the player saved with `store`
+    pub fn load_stored(name: &str) -> Result<Blueprint, GameError> {
+        let storage = quad_storage::STORAGE.lock().unwrap();
+        let json = storage
+            .get(&format!("{STORAGE_PREFIX}{name}"))
+            .ok_or(BlueprintError::NotStored(name.to_owned()))?;
+        Blueprint::from_json(&json)
+    }
+
+    pub fn store(&self) {
+        let mut storage = quad_storage::STORAGE.lock().unwrap();
+        storage.set(&format!("{STORAGE_PREFIX}{}", self.name), &self.serialize_json());
+    }
+
+    pub fn from_json(json: &str) -> Result<Blueprint, GameError> {
+        let blueprint: Blueprint = DeJson::deserialize_json(json)?;
+        blueprint.validate()?;
+        Ok(blueprint)
+    }
+
+    pub fn material(&self) -> Result<TM, BlueprintError> {
+        [TM::Cardboard, TM::Wood1, TM::Wood2, TM::Steel, TM::Space]
+            .into_iter()
+            .find(|material| material.to_string().eq_ignore_ascii_case(&self.material))
+            .ok_or(BlueprintError::UnknownMaterial(self.material.clone()))
+    }
+
+    pub fn release(&self) -> Result<(ReleaseMode, f32), BlueprintError> {
+        let mode = match self.sling.release.to_lowercase().as_str() {
+            "pin" => ReleaseMode::Pin,
+            "arm" => ReleaseMode::Arm,
+            _ => return Err(BlueprintError::UnknownRelease(self.sling.release.clone())),
+        };
+        let range = mode.angle_range();
+        match (range.start..=range.end).contains(&self.sling.angle) {
+            true => Ok((mode, self.sling.angle.to_radians())),
+            false => Err(BlueprintError::ReleaseAngle(self.sling.angle)),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), BlueprintError> {
+        let positive = [
+            ("height", self.height),
+            ("arm.long", self.arm.long),
+            ("arm.short", self.arm.short),
+            ("weight.length", self.weight.length),
+            ("weight.mass", self.weight.mass),
+            ("sling.length", self.sling.length),
+            ("projectile", self.projectile),
+        ];
+        if let Some((field, _)) = positive.iter().find(|(_, x)| !(x.is_finite() && *x > 0.0)) {
+            return Err(BlueprintError::NotPositive(field));
+        }
+        if self.arm.long <= self.height {
+            return Err(BlueprintError::ArmTooShort);
+        }
+        // Same workshop limits the editor holds designs to
+        let material = self.material()?;
+        if self.arm.long + self.arm.short > material.max_arm() {
+            return Err(BlueprintError::ArmTooLong(material.max_arm()));
+        }
+        if self.weight.mass > material.max_weight() {
+            return Err(BlueprintError::TooHeavy(material.max_weight()));
+        }
+        self.release()?;
+        Ok(())
+    }
+
+    /// Fails with the first tech the design needs that `have` says is missing
+    pub fn check_unlocked(&self, have: impl Fn(&str) -> bool) -> Result<(), BlueprintError> {
+        let material = self.material()?;
+        let (mode, angle) = self.release()?;
+        let fixed_pin = TrebuchetSling::new(1.0);
+        let mut needs = Vec::new();
+        if material != TM::Cardboard {
+            needs.push(material.to_string());
+        }
+        if self.wheels {
+            needs.push("Wheels".to_owned());
+        }
+        if !self.weight.hinged {
+            needs.push("Fixed Weight".to_owned());
+        }
+        match mode {
+            ReleaseMode::Arm => needs.push("Release Trigger".to_owned()),
+            ReleaseMode::Pin if (angle - fixed_pin.release_angle).abs() > 1e-4 => {
+                needs.push("Release Pin".to_owned())
+            }
+            ReleaseMode::Pin => {}
+        }
+        match needs.into_iter().find(|tech| !have(tech)) {
+            Some(tech) => Err(BlueprintError::Locked(tech)),
+            None => Ok(()),
+        }
+    }
+
+    /// Builder for this design at `position`, wheels still need the ground's resistance
+    pub fn builder(&self, position: I64Vec2) -> Result<TrebuchetBuilder, BlueprintError> {
+        self.validate()?;
+        let material = self.material()?;
+        let (mode, angle) = self.release()?;
+        let weight = match self.weight.hinged {
+            true => TrebuchetWeight::new(self.weight.length, self.weight.mass, material),
+            false => TrebuchetWeight::fixed(self.weight.length, self.weight.mass, material),
+        };
+        let builder = Trebuchet::init(position)
+            .base(self.height, material)
+            .arm(TrebuchetArm::new(self.arm.long, self.arm.short, material))
+            .weight(weight)
+            .sling(self.sling.length)
+            .release(mode, angle)
+            .projectile(self.projectile);
+        Ok(match self.wheels {
+            true => builder.wheels(0.0),
+            false => builder,
+        })
+    }
+}
+
+#[cfg(test)]
+mod blueprint_test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let trebuchet = Trebuchet::init(I64Vec2::ZERO)
+            .base(1.0, TM::Wood1)
+            .weight(TrebuchetWeight::fixed(0.3, 80.0, TM::Wood1))
+            .release(ReleaseMode::Arm, 0.2)
+            .build();
+        let json = Blueprint::from_trebuchet("test", &trebuchet).serialize_json();
+        let blueprint = Blueprint::from_json(&json).unwrap();
+        let rebuilt = blueprint.builder(I64Vec2::ZERO).unwrap().build();
+
+        assert_eq!(rebuilt.material, TM::Wood1);
+        assert!(!rebuilt.weight.hinged);
+        assert_eq!(rebuilt.sling.release_mode, ReleaseMode::Arm);
+        assert!((rebuilt.sling.release_angle - 0.2).abs() < 1e-6);
+        assert_eq!(rebuilt.arm.long_length, trebuchet.arm.long_length);
+    }
+
+    #[test]
+    fn invalid() {
+        let trebuchet = Trebuchet::init(I64Vec2::ZERO).build();
+        let mut blueprint = Blueprint::from_trebuchet("test", &trebuchet);
+        blueprint.material = "Cheese".to_owned();
+        assert_eq!(
+            blueprint.validate(),
+            Err(BlueprintError::UnknownMaterial("Cheese".to_owned()))
+        );
+
+        let mut blueprint = Blueprint::from_trebuchet("test", &trebuchet);
+        blueprint.arm.long = 0.5;
+        assert_eq!(blueprint.validate(), Err(BlueprintError::ArmTooShort));
+
+        blueprint.weight.mass = -1.0;
+        assert_eq!(blueprint.validate(), Err(BlueprintError::NotPositive("weight.mass")));
+
+        let mut blueprint = Blueprint::from_trebuchet("test", &trebuchet);
+        blueprint.sling.angle = 0.0;
+        assert_eq!(blueprint.validate(), Err(BlueprintError::ReleaseAngle(0.0)));
+
+        assert!(Blueprint::from_json("{\"name\": 1}").is_err());
+    }
+
+    #[test]
+    fn oversized() {
+        let trebuchet = Trebuchet::init(I64Vec2::ZERO).build();
+        let mut blueprint = Blueprint::from_trebuchet("test", &trebuchet);
+        blueprint.arm.long = TM::Cardboard.max_arm();
+        assert!(matches!(
+            Blueprint::from_json(&blueprint.serialize_json()),
+            Err(GameError::BPError(BlueprintError::ArmTooLong(_)))
+        ));
+
+        let mut blueprint = Blueprint::from_trebuchet("test", &trebuchet);
+        blueprint.weight.mass = TM::Cardboard.max_weight() + 1.0;
+        assert!(matches!(
+            Blueprint::from_json(&blueprint.serialize_json()),
+            Err(GameError::BPError(BlueprintError::TooHeavy(_)))
+        ));
+    }
+
+    #[test]
+    fn locked() {
+        let trebuchet = Trebuchet::init(I64Vec2::ZERO)
+            .base(1.0, TM::Wood1)
+            .release(ReleaseMode::Arm, 0.2)
+            .build();
+        let blueprint = Blueprint::from_trebuchet("test", &trebuchet);
+        assert_eq!(
+            blueprint.check_unlocked(|_| false),
+            Err(BlueprintError::Locked("Wood1".to_owned()))
+        );
+        assert_eq!(
+            blueprint.check_unlocked(|tech| tech == "Wood1"),
+            Err(BlueprintError::Locked("Release Trigger".to_owned()))
+        );
+        assert_eq!(blueprint.check_unlocked(|_| true), Ok(()));
+
+        let starter = Blueprint::from_trebuchet("test", &Trebuchet::init(I64Vec2::ZERO).build());
+        assert_eq!(starter.check_unlocked(|_| false), Ok(()));
+    }
+}
@@ -0,0 +1,204 @@
+use super::*;
+use crate::physics::PHYSICS_TICK;
+
+const REFINE_ROUNDS: usize = 4;
+const REFINE_SPREAD: f32 = 0.1;
+// Shortest short arm a design can have
+const MIN_SHORT: Meters = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    ReleaseSpeed,
+    Range,
+}
+
+/// Fixed limits the optimizer has to design within
+#[derive(Debug, Clone, Copy)]
+pub struct Constraints {
+    pub arm_budget: Meters,
+    pub max_weight: Kilograms,
+    pub height:     Meters,
+    pub material:   TrebuchetMaterial,
+}
+
+impl Constraints {
+    /// Long arm reaching the ground plus the shortest short arm fit in the budget
+    pub fn feasible(&self) -> bool {
+        self.height * 1.1 + MIN_SHORT <= self.arm_budget
+    }
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Constraints {
+            arm_budget: 2.0,
+            max_weight: 50.0,
+            height:     1.0,
+            material:   TM::Cardboard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Design {
+    pub long_length:   Meters,
+    pub short_length:  Meters,
+    pub weight_length: Meters,
+    pub weight_mass:   Kilograms,
+    pub sling_length:  Meters,
+}
+
+impl Design {
+    pub fn builder(&self, constraints: &Constraints) -> TrebuchetBuilder {
+        Trebuchet::init(I64Vec2::ZERO)
+            .base(constraints.height, constraints.material)
+            .arm(TrebuchetArm::new(
+                self.long_length,
+                self.short_length,
+                constraints.material,
+            ))
+            .weight(TrebuchetWeight::new(
+                self.weight_length,
+                self.weight_mass,
+                constraints.material,
+            ))
+            .sling(self.sling_length)
+    }
+
+    /// Pulls a design back inside the constraints
+    fn clamp(mut self, constraints: &Constraints) -> Design {
+        let Constraints { arm_budget, max_weight, height, .. } = *constraints;
+        // Long arm has to reach the ground for the sling to start on it, total length is clamped last
+        self.long_length = self.long_length.min(arm_budget * 0.95).max(height * 1.1)
+            .min(arm_budget - MIN_SHORT);
+        self.short_length = self.short_length.clamp(MIN_SHORT, arm_budget - self.long_length);
+        // Hanging weight must clear the ground at the bottom of its swing
+        self.weight_length = self.weight_length.clamp(0.05, (height - self.short_length).max(0.05));
+        self.weight_mass = self.weight_mass.clamp(0.1 * max_weight, max_weight);
+        self.sling_length = self.sling_length.clamp(0.25 * self.long_length, 1.5 * self.long_length);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate {
+    pub design: Design,
+    pub report: LaunchReport,
+    pub score:  f32,
+}
+
+/// Searches arm, weight and sling parameters for the best designs under the constraints.
+/// Same seed gives the same ranking, best candidate first, empty if the constraints can't be met.
+pub fn optimize(
+    constraints: &Constraints,
+    objective: Objective,
+    seed: u64,
+    samples: usize,
+) -> Vec<Candidate> {
+    if !constraints.feasible() {
+        return Vec::new();
+    }
+    let mut rng = Rng::new(seed);
+    let mut candidates: Vec<Candidate> = (0..samples)
+        .filter_map(|_| {
+            let budget = constraints.arm_budget;
+            let long_length = budget * rng.range(0.5, 0.95);
+            let design = Design {
+                long_length,
+                short_length: budget - long_length,
+                weight_length: rng.range(0.05, constraints.height),
+                weight_mass: constraints.max_weight * rng.range(0.1, 1.0),
+                sling_length: long_length * rng.range(0.25, 1.5),
+            };
+            evaluate(design.clamp(constraints), constraints, objective)
+        })
+        .collect();
+    rank(&mut candidates);
+
+    // Nudge the best few around to settle on a local optimum
+    let mut spread = REFINE_SPREAD;
+    for _ in 0..REFINE_ROUNDS {
+        let leaders: Vec<Design> = candidates.iter().take(4).map(|c| c.design).collect();
+        for design in leaders {
+            for _ in 0..samples / 16 + 1 {
+                let mut jiggle = |x: f32| x * (1.0 + rng.range(-spread, spread));
+                let nudged = Design {
+                    long_length:   jiggle(design.long_length),
+                    short_length:  jiggle(design.short_length),
+                    weight_length: jiggle(design.weight_length),
+                    weight_mass:   jiggle(design.weight_mass),
+                    sling_length:  jiggle(design.sling_length),
+                };
+                candidates.extend(evaluate(nudged.clamp(constraints), constraints, objective));
+            }
+        }
+        rank(&mut candidates);
+        spread /= 2.0;
+    }
+
+    candidates
+}
+
+fn evaluate(design: Design, constraints: &Constraints, objective: Objective) -> Option<Candidate> {
+    let report = launch_report(design.builder(constraints), PHYSICS_TICK)?;
+    if report.breakage.is_some() {
+        return None;
+    }
+    let score = match objective {
+        Objective::ReleaseSpeed => report.release_velocity.length(),
+        Objective::Range => report.range(),
+    };
+    score.is_finite().then_some(Candidate { design, report, score })
+}
+
+fn rank(candidates: &mut [Candidate]) {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+// Own xorshift so searches stay reproducible without touching the global macroquad rand state
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn range(&mut self, low: f32, high: f32) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        let unit = (self.0 >> 40) as f32 / (1u64 << 24) as f32;
+        low + (high - low) * unit
+    }
+}
+
+#[cfg(test)]
+mod optimizer_test {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let constraints = Constraints::default();
+        let a = optimize(&constraints, Objective::Range, 7, 64);
+        let b = optimize(&constraints, Objective::Range, 7, 64);
+        assert_eq!(a[0].design, b[0].design);
+        assert!(a.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let best = a[0].design;
+        assert!(best.long_length + best.short_length <= constraints.arm_budget + 1e-4);
+        assert!(best.weight_mass <= constraints.max_weight);
+    }
+
+    #[test]
+    fn tall_base() {
+        let tall = Constraints { height: 2.0, ..Default::default() };
+        assert!(!tall.feasible());
+        assert!(optimize(&tall, Objective::Range, 7, 16).is_empty());
+
+        let snug = Constraints { height: 1.5, ..Default::default() };
+        for candidate in optimize(&snug, Objective::Range, 7, 32) {
+            let design = candidate.design;
+            assert!(design.long_length + design.short_length <= snug.arm_budget + 1e-4);
+        }
+    }
+}
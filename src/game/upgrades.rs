@@ -1,11 +1,60 @@
-#[allow(dead_code)]
+use macroquad::math::{I64Vec2, Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Gun {
     Pistol,
     Machine,
     Railgun,
 }
 
-#[allow(dead_code)]
+impl Gun {
+    /// Speed of a round leaving the barrel, m/s
+    pub fn muzzle_velocity(&self) -> f32 {
+        match self {
+            Gun::Pistol => 350.0,
+            Gun::Machine => 400.0,
+            Gun::Railgun => 3000.0,
+        }
+    }
+
+    /// Kg
+    pub fn round_mass(&self) -> f32 {
+        match self {
+            Gun::Pistol => 0.008,
+            Gun::Machine => 0.01,
+            Gun::Railgun => 0.05,
+        }
+    }
+
+    /// Seconds between shots with the trigger held
+    pub fn fire_interval(&self) -> f32 {
+        match self {
+            Gun::Pistol => 0.5,
+            Gun::Machine => 0.05,
+            Gun::Railgun => 2.0,
+        }
+    }
+
+    /// Rounds the buckos make out of one rock
+    pub fn rounds_per_rock(&self) -> u32 {
+        match self {
+            Gun::Pistol => 12,
+            Gun::Machine => 30,
+            Gun::Railgun => 2,
+        }
+    }
+}
+
+/// Round in flight after leaving the gun
+#[derive(Debug, Clone, Copy)]
+pub struct Shot {
+    pub position: I64Vec2,
+    pub velocity: Vec2,
+    /// Seconds since it was fired
+    pub age:      f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Melee {
     Halberd,
     Screw,
@@ -13,24 +62,90 @@ pub enum Melee {
 }
 
 #[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Coating {
+    Streamlined,
     Asbestos,
 }
 
-#[allow(dead_code)]
+impl Coating {
+    /// Multiplies the plushie's drag coefficient
+    pub fn drag_factor(&self) -> f32 {
+        match self {
+            Coating::Streamlined => 0.4,
+            Coating::Asbestos => 1.1,
+        }
+    }
+
+    /// Temperature the plushie survives with this coating, K, never below the bare stuffing's
+    pub fn heat_limit(&self) -> f32 {
+        match self {
+            Coating::Streamlined => 600.0,
+            Coating::Asbestos => 1400.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Brakes {
     Parachute,
     Rocket,
 }
 
-#[allow(dead_code)]
+/// How far the brakes have got during a flight
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum BrakeState {
+    #[default]
+    Stowed,
+    Deployed,
+    Torn,
+    Spent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Thruster {
     Bean,
     Impulse,
 }
 
-#[allow(dead_code)]
+impl Thruster {
+    /// Push at full throttle, N
+    pub fn thrust(&self) -> f32 {
+        match self {
+            Thruster::Bean => 20.0,
+            Thruster::Impulse => 100.0,
+        }
+    }
+
+    /// Specific impulse, seconds of thrust per weight of beans
+    pub fn isp(&self) -> f32 {
+        match self {
+            Thruster::Bean => 60.0,
+            Thruster::Impulse => 250.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Stabilizer {
     Wings,
     Rocket,
+}
+
+impl Stabilizer {
+    /// Fin area weathervaning the plushie into the airflow, m^2
+    pub fn fin_area(&self) -> f32 {
+        match self {
+            Stabilizer::Wings => 0.02,
+            Stabilizer::Rocket => 0.0,
+        }
+    }
+
+    /// Torque the reaction jets put against any spin, works in vacuum too, N m
+    pub fn jet_torque(&self) -> f32 {
+        match self {
+            Stabilizer::Wings => 0.0,
+            Stabilizer::Rocket => 0.05,
+        }
+    }
 }
\ No newline at end of file
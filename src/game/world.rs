@@ -8,6 +8,8 @@ pub mod perlin;
 pub mod terrain;
 
 const GRAVITATION: f32 = 6.6743e-11;
+// Air thinner than this many scale heights up is treated as vacuum
+const ATMOSPHERE_DEPTH: f32 = 12.0;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -16,8 +18,24 @@ pub enum WorldClass {
     Desert,
     Demon,
     Gas,
+    /// Airless rock, moons and the like
+    Barren,
 }
 
+impl WorldClass {
+    /// Air density at sea level in kg/m^3 and its scale height at real size
+    fn atmosphere(&self) -> (f32, Meters) {
+        match self {
+            WorldClass::Minshara => (1.225, 8_500.0),
+            WorldClass::Desert => (0.02, 11_000.0),
+            WorldClass::Demon => (65.0, 15_900.0),
+            WorldClass::Gas => (0.16, 27_000.0),
+            WorldClass::Barren => (0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct World {
     // pub system:   IVec2,   // lightyears
     pub position: I64Vec2, // 1/256 meters
@@ -27,6 +45,12 @@ pub struct World {
     pub height_map: Vec<Meters>,
     pub class_map:  Vec<(TerrainClass, usize)>,
     pub class:      WorldClass,
+
+    /// Air density at sea level where the exponential atmosphere starts, kg/m^3, not the water's
+    pub surface_air_density: f32,
+    pub scale_height:        Meters,
+    /// Sphere of influence radius, unbounded for the body everything else orbits
+    pub soi:                 Meters,
 }
 
 impl World {
@@ -77,6 +101,8 @@ impl World {
             current_index += length;
         }
 
+        let (surface_air_density, scale_height) = class.atmosphere();
+
         World {
             // system,
             position,
@@ -85,23 +111,85 @@ impl World {
             height_map,
             class_map,
             class,
+            surface_air_density,
+            scale_height: scale_height * scale,
+            soi: f32::INFINITY,
         }
     }
 
+    /// Places the world `distance` from `parent` at `angle` and sizes its sphere of influence
+    pub fn orbiting(mut self, parent: &World, distance: Meters, angle: f32) -> World {
+        self.position = parent.position + polar_to_cartesian(distance, angle).to_i64coords();
+        self.soi = distance * (self.mass / parent.mass).powf(0.4);
+        self
+    }
+
+    /// G times mass, m^3/s^2
+    pub fn gravitational_parameter(&self) -> f32 {
+        GRAVITATION * self.mass
+    }
+
+    /// Altitude above which the air is treated as vacuum
+    pub fn atmosphere_height(&self) -> Meters {
+        ATMOSPHERE_DEPTH * self.scale_height
+    }
+
     pub fn grativy_at(&self, point: I64Vec2) -> Vec2 {
         let r = (point - self.position).to_meters();
         -r.normalize_or_zero() * GRAVITATION * self.mass / r.length_squared()
     }
 
+    /// Exponential atmosphere thinning out with altitude, kg/m^3
+    pub fn density_at(&self, point: I64Vec2) -> f32 {
+        let altitude = self.altitude_at(point).max(0.0);
+        match altitude < self.atmosphere_height() {
+            true => self.surface_air_density * (-altitude / self.scale_height).exp(),
+            false => 0.0,
+        }
+    }
+
+    /// How far below the sea surface `point` is, zero on land or above water
+    pub fn water_depth(&self, point: I64Vec2) -> Meters {
+        match self.terrain_class(self.terrain_index_beneath(point)) {
+            TerrainClass::Ocean => (-self.altitude_at(point)).max(0.0),
+            _ => 0.0,
+        }
+    }
+
     pub fn altitude_at(&self, point: I64Vec2) -> Meters {
         (point - self.position).to_meters().length() - self.radius
     }
 
+    /// Height above the ground or sea surface beneath `point`
+    pub fn clearance(&self, point: I64Vec2) -> Meters {
+        self.altitude_at(point) - self.height_map[self.terrain_index_beneath(point)].max(0.0)
+    }
+
     pub fn terrain_index_beneath(&self, point: I64Vec2) -> Kilometers {
         (self.radius / 1000.0 * (point - self.position).to_meters().to_angle_tau()) as Kilometers
             % self.height_map.len()
     }
 
+    /// Outward normal blended between the vertex normals at both ends of the segment under `point`
+    pub fn surface_normal(&self, point: I64Vec2) -> Vec2 {
+        let circ = self.height_map.len();
+        let position = self.radius / 1000.0 * (point - self.position).to_meters().to_angle_tau();
+        let index = position as Kilometers % circ;
+        // Surface runs anticlockwise with the index, outward is on its right
+        let segment = |i: Kilometers| {
+            -(self.surface((i + 1) % circ) - self.surface(i))
+                .to_meters()
+                .perp()
+                .normalize()
+        };
+        let before = segment((index + circ - 1) % circ);
+        let current = segment(index);
+        let after = segment((index + 1) % circ);
+        (before + current)
+            .lerp(current + after, position.fract())
+            .normalize()
+    }
+
     pub fn terrain_class(&self, index: Kilometers) -> TerrainClass {
         self.class_map
             .iter()
@@ -124,4 +212,73 @@ impl World {
             .to_i64coords()
             + self.position
     }
+
+    /// Point `depth` under the surface between vertices at `angle` around the body,
+    /// f64 so steps of a few millimeters aren't lost against the radius
+    pub fn point_under_surface(&self, angle: f64, depth: Meters) -> I64Vec2 {
+        let circ = self.height_map.len();
+        let along = angle.rem_euclid(std::f64::consts::TAU) * (self.radius / 1000.0) as f64;
+        let index = along as Kilometers % circ;
+        let height = self.height_map[index]
+            + (self.height_map[(index + 1) % circ] - self.height_map[index]) * along.fract() as f32;
+        let distance = (self.radius + height - depth) as f64 * 256.0;
+        self.position + (DVec2::from_angle(angle) * distance).round().as_i64vec2()
+    }
+}
+
+/// Body whose sphere of influence `point` is in, falling back to the planet outside every moon's
+pub fn body_at<'a>(planet: &'a World, moons: &'a [World], point: I64Vec2) -> &'a World {
+    moons
+        .iter()
+        .find(|moon| (point - moon.position).to_meters().length() < moon.soi)
+        .unwrap_or(planet)
+}
+
+#[cfg(test)]
+mod world_test {
+    use super::*;
+
+    fn earth() -> World {
+        World::new(0.01, I64Vec2::ZERO, 6_371_000.0, 5.972e+24, WorldClass::Minshara, None)
+    }
+
+    #[test]
+    fn atmosphere() {
+        let world = earth();
+        let above = |altitude: Meters| to_i64coords(vec2(0.0, world.radius + altitude));
+
+        assert!((world.density_at(above(0.0)) - 1.225).abs() < 1e-3);
+        let thinner = world.density_at(above(world.scale_height));
+        assert!((thinner - 1.225 / consts::E).abs() < 1e-3);
+        assert_eq!(world.density_at(above(ATMOSPHERE_DEPTH * world.scale_height + 1.0)), 0.0);
+    }
+
+    #[test]
+    fn sphere_of_influence() {
+        let planet = earth();
+        let moon = World::new(0.01, I64Vec2::ZERO, 1_737_400.0, 7.342e+22, WorldClass::Barren, None)
+            .orbiting(&planet, 3_844_000.0, 0.0);
+        // Roughly 66 000 km at real size
+        assert!((moon.soi - 661_000.0).abs() < 10_000.0, "soi {}", moon.soi);
+        assert_eq!(moon.density_at(moon.surface(0)), 0.0);
+
+        let moons = [moon];
+        let near_moon = moons[0].position + to_i64coords(vec2(-moons[0].soi * 0.9, 0.0));
+        let between = moons[0].position + to_i64coords(vec2(-moons[0].soi * 1.1, 0.0));
+        assert_eq!(body_at(&planet, &moons, near_moon).position, moons[0].position);
+        assert_eq!(body_at(&planet, &moons, between).position, planet.position);
+    }
+
+    #[test]
+    fn under_surface() {
+        let world = earth();
+        let angle = |index: Kilometers| index as f64 * 1000.0 / world.radius as f64;
+        let at_vertex = world.point_under_surface(angle(7), 0.0);
+        assert!((at_vertex - world.surface(7)).to_meters().length() < 0.05);
+
+        // Halfway along a segment sits between the vertex heights
+        let halfway = world.point_under_surface((angle(7) + angle(8)) / 2.0, 1.0);
+        let expected = (world.height_map[7] + world.height_map[8]) / 2.0 - 1.0;
+        assert!((world.altitude_at(halfway) - expected).abs() < 0.05);
+    }
 }
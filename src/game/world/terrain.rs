@@ -25,6 +25,52 @@ pub enum TerrainClass {
     Ocean,
 }
 
+impl TerrainClass {
+    /// Share of the weight resisting anything rolling over the ground
+    pub fn rolling_resistance(&self) -> f32 {
+        match self {
+            TerrainClass::Plain => 0.05,
+            TerrainClass::Hills => 0.08,
+            TerrainClass::Sands => 0.3,
+            TerrainClass::Rocky => 0.1,
+            TerrainClass::Ocean => 0.5,
+        }
+    }
+
+    /// Share of the speed into the ground kept after a bounce
+    pub fn restitution(&self) -> f32 {
+        match self {
+            TerrainClass::Plain => 0.6,
+            TerrainClass::Hills => 0.45,
+            TerrainClass::Sands => 0.1,
+            TerrainClass::Rocky => 0.35,
+            TerrainClass::Ocean => 0.2,
+        }
+    }
+
+    /// Coulomb friction coefficient for sliding along the ground
+    pub fn friction(&self) -> f32 {
+        match self {
+            TerrainClass::Plain => 0.3,
+            TerrainClass::Hills => 0.4,
+            TerrainClass::Sands => 0.9,
+            TerrainClass::Rocky => 0.6,
+            TerrainClass::Ocean => 0.5,
+        }
+    }
+
+    /// Slowdown of a screw digging through the ground, m/s^2
+    pub fn burrow_drag(&self) -> f32 {
+        match self {
+            TerrainClass::Plain => 15.0,
+            TerrainClass::Hills => 20.0,
+            TerrainClass::Sands => 5.0,
+            TerrainClass::Rocky => 60.0,
+            TerrainClass::Ocean => 10.0,
+        }
+    }
+}
+
 pub fn gen_height_map(circ: Kilometers, sections: &[TerrainSection], scale: f32) -> Vec<Meters> {
     let ami_cute = u64::from_le_bytes("ami cute".as_bytes().try_into().unwrap());
     let noise = PerlinNoise::new(ami_cute, circ);
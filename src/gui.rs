@@ -1,6 +1,6 @@
 use crate::{Game, GameError};
 
-use landed::landed;
+use landed::*;
 use launched::launched;
 use paused::paused;
 use prelaunch::*;
@@ -24,7 +24,7 @@ pub enum Scene {
     Paused,
     PreLaunch(PreLaunchState),
     Launched,
-    Landed,
+    Landed(LandedState),
     Settings(Box<Scene>),
 }
 
@@ -48,7 +48,7 @@ impl Gui {
             Scene::Paused => paused(&self.assets[PAUSED], game),
             Scene::PreLaunch(state) => prelaunch(&self.assets[PRELAUNCH], state, game),
             Scene::Launched => launched(&self.assets[LAUNCHED], game),
-            Scene::Landed => landed(&self.assets[LANDED], game),
+            Scene::Landed(state) => landed(&self.assets[LANDED], state, game),
             Scene::Settings(last_scene) => settings(&self.assets[SETTINGS], last_scene, game),
         };
     }
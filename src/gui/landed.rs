@@ -7,38 +7,119 @@ use super::{PreLaunchState, SceneAssets};
 const WINDOW_SIZE: Vec2 = vec2(800.0, 600.0);
 const BUTTON_SIZE: Vec2 = vec2(200.0, 60.0);
 const BUTTON_OFFSET: Vec2 = vec2(0.0, 240.0);
+const REPLAY_OFFSET: Vec2 = vec2(-240.0, 240.0);
+const REPLAY_BAR_SIZE: Vec2 = vec2(800.0, 160.0);
 
-pub fn landed(assets: &SceneAssets, game: &mut Game) -> Scene {
+#[derive(Debug, Clone, Copy)]
+pub struct LandedState {
+    playing: bool,
+    /// Playback rate, 1.0 is real time
+    speed:   f32,
+}
+
+impl Default for LandedState {
+    fn default() -> Self {
+        LandedState {
+            playing: true,
+            speed:   0.1,
+        }
+    }
+}
+
+pub fn landed(assets: &SceneAssets, mut state: LandedState, game: &mut Game) -> Scene {
     root_ui().push_skin(&assets.skin);
-    let window_pos = (get_screen() - WINDOW_SIZE) / 2.0;
 
     let mut next_scene = None;
-    widgets::Window::new(hash!(), window_pos, WINDOW_SIZE)
-        .titlebar(false)
-        .movable(false)
-        .ui(&mut root_ui(), |ui| {
-            // for stat in game.stats.as_vec() {
-            //     ui.label(None, &stat.field)
-            // }
-            // ui.separator();
-
-            // let thing = "Research Earned";
-            // let calc = ui.calc_size(thing);
-            // ui.label(WINDOW_SIZE / 2.0 + vec2(-170.0, 120.0), thing);
-            if widgets::Button::new("Next Day")
-                .position((WINDOW_SIZE - BUTTON_SIZE) / 2.0 + BUTTON_OFFSET)
-                .size(BUTTON_SIZE)
-                .ui(ui)
-            {
-                game.next_day();
-                next_scene = Some(Scene::PreLaunch(PreLaunchState::default()))
-            }
-        });
+    match game.replay.cursor {
+        Some(_) => replay(&mut state, game),
+        None => {
+            let window_pos = (get_screen() - WINDOW_SIZE) / 2.0;
+            widgets::Window::new(hash!(), window_pos, WINDOW_SIZE)
+                .titlebar(false)
+                .movable(false)
+                .ui(&mut root_ui(), |ui| {
+                    // for stat in game.stats.as_vec() {
+                    //     ui.label(None, &stat.field)
+                    // }
+                    // ui.separator();
+
+                    // let thing = "Research Earned";
+                    // let calc = ui.calc_size(thing);
+                    // ui.label(WINDOW_SIZE / 2.0 + vec2(-170.0, 120.0), thing);
+                    if !game.replay.is_empty()
+                        && widgets::Button::new("Replay")
+                            .position((WINDOW_SIZE - BUTTON_SIZE) / 2.0 + REPLAY_OFFSET)
+                            .size(BUTTON_SIZE)
+                            .ui(ui)
+                    {
+                        game.replay.cursor = Some(0.0);
+                        state.playing = true;
+                    }
+
+                    if widgets::Button::new("Next Day")
+                        .position((WINDOW_SIZE - BUTTON_SIZE) / 2.0 + BUTTON_OFFSET)
+                        .size(BUTTON_SIZE)
+                        .ui(ui)
+                    {
+                        game.next_day();
+                        next_scene = Some(Scene::PreLaunch(PreLaunchState::default()))
+                    }
+                });
+        }
+    }
 
     root_ui().pop_skin();
 
     match next_scene {
         Some(scene) => scene,
-        None => Scene::Landed,
+        None => Scene::Landed(state),
+    }
+}
+
+/// Playback bar along the bottom of the screen
+fn replay(state: &mut LandedState, game: &mut Game) {
+    let duration = game.replay.duration();
+    let mut cursor = game.replay.cursor.unwrap_or_default();
+    if state.playing {
+        cursor += get_frame_time() * state.speed;
+        if cursor >= duration {
+            cursor = duration;
+            state.playing = false;
+        }
     }
+
+    let bar_pos = vec2(
+        (screen_width() - REPLAY_BAR_SIZE.x) / 2.0,
+        screen_height() - REPLAY_BAR_SIZE.y,
+    );
+    let mut close = false;
+    widgets::Window::new(hash!(), bar_pos, REPLAY_BAR_SIZE)
+        .titlebar(false)
+        .movable(false)
+        .ui(&mut root_ui(), |ui| {
+            widgets::Slider::new(hash!(), 0.0..duration)
+                .label("Time")
+                .ui(ui, &mut cursor);
+            widgets::Slider::new(hash!(), 0.01..1.0)
+                .label("Speed")
+                .ui(ui, &mut state.speed);
+
+            let label = match state.playing {
+                true => "Pause",
+                false => "Play",
+            };
+            if widgets::Button::new(label).ui(ui) {
+                if !state.playing && cursor >= duration {
+                    cursor = 0.0;
+                }
+                state.playing ^= true;
+            }
+            ui.same_line(0.0);
+            close = widgets::Button::new("Back").ui(ui);
+        });
+
+    game.replay.cursor = match close {
+        true => None,
+        false => Some(cursor.clamp(0.0, duration)),
+    };
 }
@@ -1,10 +1,11 @@
-use crate::{Game, GameState, Scene};
+use crate::{Game, GameState, Scene, WARP_LEVELS};
 use macroquad::prelude::*;
 use macroquad::ui::{root_ui, widgets};
 
-use super::SceneAssets;
+use super::{LandedState, SceneAssets};
 
 const BUTTON_SIZE: Vec2 = vec2(240.0, 80.0);
+const WARP_BUTTON_SIZE: Vec2 = vec2(120.0, 60.0);
 
 pub fn launched(assets: &SceneAssets, game: &mut Game) -> Scene {
     root_ui().push_skin(&assets.skin);
@@ -21,8 +22,28 @@ pub fn launched(assets: &SceneAssets, game: &mut Game) -> Scene {
         next_scene = Some(Scene::Paused);
     }
 
+    let slower = widgets::Button::new("<<")
+        .position(vec2(screen_width() - BUTTON_SIZE.x, BUTTON_SIZE.y))
+        .size(WARP_BUTTON_SIZE)
+        .ui(&mut root_ui())
+        || is_key_pressed(KeyCode::Comma);
+    let faster = widgets::Button::new(">>")
+        .position(vec2(
+            screen_width() - BUTTON_SIZE.x + WARP_BUTTON_SIZE.x,
+            BUTTON_SIZE.y,
+        ))
+        .size(WARP_BUTTON_SIZE)
+        .ui(&mut root_ui())
+        || is_key_pressed(KeyCode::Period);
+    if slower {
+        game.warp = game.warp.saturating_sub(1);
+    }
+    if faster {
+        game.warp = (game.warp + 1).min(WARP_LEVELS.len() - 1);
+    }
+
     if game.state == GameState::Landed {
-        next_scene = Some(Scene::Landed);
+        next_scene = Some(Scene::Landed(LandedState::default()));
     }
 
     root_ui().pop_skin();
@@ -1,4 +1,12 @@
-use crate::{get_screen, Game, GameState, Scene};
+use crate::{
+    get_screen,
+    launcher::{EngineKind, Launcher, SiegeEngine},
+    launcher::Breakage,
+    physics::PHYSICS_TICK,
+    trebuchet::{blueprint::Blueprint, launch_report, ReleaseMode},
+    upgrades::{Brakes, Coating, Gun, Melee, Stabilizer, Thruster},
+    Game, Scene,
+};
 use macroquad::prelude::*;
 use macroquad::ui::{hash, root_ui, widgets, Ui};
 
@@ -14,11 +22,26 @@ const TAB_TREBUCHET: u32 = 1;
 const TAB_PLAYER: u32 = 2;
 const TAB_LOGS: u32 = 3;
 
-#[derive(Debug, Clone, Copy, Default)]
+// Single player storage slot until blueprints get names
+const SAVED_BLUEPRINT: &str = "saved";
+
+#[derive(Debug, Clone, Default)]
 pub struct PreLaunchState {
     upgrades: bool,
     tab:      u32,
     tech:     Option<usize>,
+    estimate: Option<Estimate>,
+    /// Last blueprint that could not be loaded or applied, and why
+    error:    Option<String>,
+}
+
+/// Outcome of a headless launch of the design on the editor
+#[derive(Debug, Clone, Copy)]
+enum Estimate {
+    Range(f32),
+    Broken(Breakage),
+    Stalled,
+    Unavailable,
 }
 
 pub fn prelaunch(assets: &SceneAssets, mut state: PreLaunchState, game: &mut Game) -> Scene {
@@ -45,8 +68,8 @@ pub fn prelaunch(assets: &SceneAssets, mut state: PreLaunchState, game: &mut Gam
                     Some(tech_index) => tech_info(ui, game, tech_index),
                     None => ui.label(None, "No tech selected"),
                 },
-                TAB_TREBUCHET => ui.label(None, &format!("{:?}", game.state)),
-                TAB_PLAYER => {}
+                TAB_TREBUCHET => trebuchet_tab(ui, game, &mut state.estimate, &mut state.error),
+                TAB_PLAYER => player_info(ui, game),
                 TAB_LOGS => {}
                 _ => unreachable!(),
             };
@@ -74,7 +97,7 @@ pub fn prelaunch(assets: &SceneAssets, mut state: PreLaunchState, game: &mut Gam
             .size(ACTION_BUTTON_SIZE)
             .ui(&mut root_ui())
         {
-            game.state = GameState::Launched;
+            game.launch();
             next_scene = Some(Scene::Launched)
         };
 
@@ -203,6 +226,401 @@ fn tech_info(ui: &mut Ui, game: &mut Game, tech_index: usize) {
     }
 }
 
+fn trebuchet_tab(
+    ui: &mut Ui,
+    game: &mut Game,
+    estimate: &mut Option<Estimate>,
+    error: &mut Option<String>,
+) {
+    let design = |game: &Game| match &game.engine {
+        SiegeEngine::Trebuchet(trebuchet) => Some(Blueprint::from_trebuchet("", trebuchet)),
+        SiegeEngine::Mangonel(_) | SiegeEngine::FloatingArm(_) => None,
+    };
+
+    let before = design(game);
+    trebuchet_info(ui, game, *estimate, error);
+    if design(game) != before {
+        *estimate = None;
+        *error = None;
+    }
+    if let Some(error) = error {
+        ui.label(None, error);
+    }
+    if estimate.is_none() {
+        *estimate = Some(estimate_range(game));
+    }
+}
+
+/// Throws the current design once without touching the game
+fn estimate_range(game: &Game) -> Estimate {
+    let SiegeEngine::Trebuchet(trebuchet) = &game.engine else {
+        return Estimate::Unavailable;
+    };
+    let Ok(builder) = Blueprint::from_trebuchet("", trebuchet).builder(I64Vec2::ZERO) else {
+        return Estimate::Unavailable;
+    };
+    let builder = match trebuchet.wheels {
+        Some(wheels) => builder.wheels(wheels.resistance),
+        None => builder,
+    };
+    match launch_report(builder, PHYSICS_TICK) {
+        Some(report) => match report.breakage {
+            Some(breakage) => Estimate::Broken(breakage),
+            None => Estimate::Range(report.range()),
+        },
+        None => Estimate::Stalled,
+    }
+}
+
+fn design_slider(ui: &mut Ui, label: &str, range: std::ops::Range<f32>, value: &mut f32) {
+    // Nothing to pick once the bounds meet
+    if range.is_empty() {
+        *value = range.start;
+        return;
+    }
+    widgets::Slider::new(hash!(label), range.clone())
+        .label(label)
+        .ui(ui, value);
+    *value = value.clamp(range.start, range.end);
+}
+
+/// Sliders for the dimensions of the trebuchet, bounded by what its material allows
+fn design_editor(ui: &mut Ui, game: &mut Game, estimate: Option<Estimate>, error: &mut Option<String>) {
+    let SiegeEngine::Trebuchet(trebuchet) = &game.engine else {
+        return;
+    };
+    let max_arm = trebuchet.material.max_arm();
+    let max_weight = trebuchet.material.max_weight();
+    let original = Blueprint::from_trebuchet("", trebuchet);
+    let mut blueprint = original.clone();
+
+    design_slider(ui, "Height", 0.5..max_arm * 0.5, &mut blueprint.height);
+    // Long arm has to reach the ground from the pivot
+    let min_long = blueprint.height * 1.05;
+    let max_long = (max_arm - 0.05).max(min_long + 0.1);
+    design_slider(ui, "Long arm", min_long..max_long, &mut blueprint.arm.long);
+    // Whole arm stays within the material, same bound as the optimizer
+    design_slider(ui, "Short arm", 0.05..max_arm - blueprint.arm.long, &mut blueprint.arm.short);
+    design_slider(ui, "Weight", 1.0..max_weight, &mut blueprint.weight.mass);
+    // Hanging weight must clear the ground at the bottom of its swing, same bound as the optimizer
+    let max_drop = (blueprint.height - blueprint.arm.short).max(0.05);
+    design_slider(ui, "Weight drop", 0.05..max_drop, &mut blueprint.weight.length);
+    design_slider(ui, "Sling", 0.2..max_arm, &mut blueprint.sling.length);
+
+    if blueprint != original {
+        if let Err(invalid) = game.load_blueprint(&blueprint) {
+            *error = Some(format!("Invalid design: {invalid}"));
+        }
+    }
+
+    // Flat ground and no air, the drawn prediction has the real landing point
+    let text = match estimate {
+        Some(Estimate::Range(range)) => format!("Vacuum range: {:.0} m", range),
+        Some(Estimate::Broken(breakage)) => format!("Vacuum range: {}", breakage),
+        Some(Estimate::Stalled) => "Vacuum range: never lets go".to_owned(),
+        Some(Estimate::Unavailable) | None => "Vacuum range: ...".to_owned(),
+    };
+    ui.label(None, &text);
+}
+
+fn trebuchet_info(ui: &mut Ui, game: &mut Game, estimate: Option<Estimate>, error: &mut Option<String>) {
+    if game.tech_tree.have("Mangonel") || game.tech_tree.have("Floating Arm") {
+        for (name, kind) in [
+            ("Trebuchet", EngineKind::Trebuchet),
+            ("Mangonel", EngineKind::Mangonel),
+            ("Floating Arm", EngineKind::FloatingArm),
+        ] {
+            if kind != EngineKind::Trebuchet && !game.tech_tree.have(name) {
+                continue;
+            }
+            if widgets::Button::new(name)
+                .selected(game.engine.kind() == kind)
+                .ui(ui)
+                && game.engine.kind() != kind
+            {
+                game.switch_engine(kind);
+            }
+        }
+    }
+
+    let release_pin = game.tech_tree.have("Release Pin");
+    let release_trigger = game.tech_tree.have("Release Trigger");
+    let wheels = game.tech_tree.have("Wheels");
+    let fixed_weight = game.tech_tree.have("Fixed Weight");
+    let SiegeEngine::Trebuchet(trebuchet) = &mut game.engine else {
+        return;
+    };
+
+    if widgets::Button::new("Save blueprint").ui(ui) {
+        Blueprint::from_trebuchet(SAVED_BLUEPRINT, trebuchet).store();
+    }
+    let mut load = None;
+    ui.same_line(0.0);
+    if widgets::Button::new("Load saved").ui(ui) {
+        load = Some(Blueprint::load_stored(SAVED_BLUEPRINT));
+    }
+    for blueprint in game.blueprints.iter() {
+        ui.same_line(0.0);
+        if widgets::Button::new(blueprint.name.as_str()).ui(ui) {
+            load = Some(Ok(blueprint.clone()));
+        }
+    }
+    if let Some(result) = load {
+        if let Err(failed) = result.and_then(|blueprint| game.load_blueprint(&blueprint)) {
+            *error = Some(format!("Could not load blueprint: {failed}"));
+        }
+        return;
+    }
+
+    design_editor(ui, game, estimate, error);
+    let SiegeEngine::Trebuchet(trebuchet) = &mut game.engine else {
+        return;
+    };
+
+    ui.label(None, &format!("Material: {}", trebuchet.material));
+    if let Some(next) = trebuchet.material.next() {
+        if game.tech_tree.have(&next.to_string())
+            && widgets::Button::new(format!("Rebuild in {} ({} rocks)", next, next.cost()).as_str())
+                .ui(ui)
+            && game.resources.rocks >= next.cost()
+        {
+            game.resources.rocks -= next.cost();
+            trebuchet.set_material(next);
+            game.player.position = trebuchet.projectile_position();
+        }
+    }
+
+    if fixed_weight {
+        let mut fixed = !trebuchet.weight.hinged;
+        widgets::Checkbox::new(hash!())
+            .label("Fixed weight")
+            .ui(ui, &mut fixed);
+        if fixed == trebuchet.weight.hinged {
+            trebuchet.weight.hinged = !fixed;
+            trebuchet.reset();
+        }
+    }
+
+    if wheels {
+        let mut rolling = trebuchet.wheels.is_some();
+        widgets::Checkbox::new(hash!())
+            .label("Wheels")
+            .ui(ui, &mut rolling);
+        if rolling != trebuchet.wheels.is_some() {
+            trebuchet.set_wheels(rolling.then_some(0.0));
+            game.settle_wheels();
+            game.player.position = game.engine.projectile_position();
+        }
+    }
+    let SiegeEngine::Trebuchet(trebuchet) = &mut game.engine else {
+        return;
+    };
+
+    if !release_pin {
+        ui.label(None, "Release pin fixed");
+        return;
+    }
+
+    let sling = &mut trebuchet.sling;
+    let mut arm_release = sling.release_mode == ReleaseMode::Arm;
+    if release_trigger {
+        widgets::Checkbox::new(hash!())
+            .label("Trigger")
+            .ui(ui, &mut arm_release);
+    }
+
+    let mode = match arm_release {
+        true => ReleaseMode::Arm,
+        false => ReleaseMode::Pin,
+    };
+    let range = mode.angle_range();
+    sling.release_mode = mode;
+
+    let mut degrees = sling.release_angle.to_degrees();
+    widgets::Slider::new(hash!(), range.clone())
+        .label("Release")
+        .ui(ui, &mut degrees);
+    sling.release_angle = degrees.clamp(range.start, range.end).to_radians();
+}
+
+fn player_info(ui: &mut Ui, game: &mut Game) {
+    ui.label(None, "Coating");
+    if widgets::Button::new("None")
+        .selected(game.player.coating.is_none())
+        .ui(ui)
+    {
+        game.player.coating = None;
+    }
+    for (name, coating) in [
+        ("Streamlined", Coating::Streamlined),
+        ("Asbestos", Coating::Asbestos),
+    ] {
+        if !game.tech_tree.have(name) {
+            continue;
+        }
+        ui.same_line(0.0);
+        if widgets::Button::new(name)
+            .selected(game.player.coating == Some(coating))
+            .ui(ui)
+        {
+            game.player.coating = Some(coating);
+        }
+    }
+    ui.label(None, &format!("Drag area: {:.4} m2", game.player.drag_area()));
+    ui.label(None, &format!("Heat limit: {:.0} K", game.player.heat_limit()));
+
+    ui.label(None, "Stabilizer");
+    if widgets::Button::new("No stabilizer")
+        .selected(game.player.stablizer.is_none())
+        .ui(ui)
+    {
+        game.player.stablizer = None;
+    }
+    for (name, stabilizer) in [
+        ("Wings", Stabilizer::Wings),
+        ("Attitude Rocket", Stabilizer::Rocket),
+    ] {
+        if !game.tech_tree.have(name) {
+            continue;
+        }
+        ui.same_line(0.0);
+        if widgets::Button::new(name)
+            .selected(game.player.stablizer == Some(stabilizer))
+            .ui(ui)
+        {
+            game.player.stablizer = Some(stabilizer);
+        }
+    }
+
+    ui.label(None, "Gun");
+    let mut gun = game.player.gun;
+    if widgets::Button::new("Unarmed")
+        .selected(gun.is_none())
+        .ui(ui)
+    {
+        gun = None;
+    }
+    for (name, choice) in [
+        ("Pistol", Gun::Pistol),
+        ("Machine Gun", Gun::Machine),
+        ("Railgun", Gun::Railgun),
+    ] {
+        if !game.tech_tree.have(name) {
+            continue;
+        }
+        ui.same_line(0.0);
+        if widgets::Button::new(name).selected(gun == Some(choice)).ui(ui) {
+            gun = Some(choice);
+        }
+    }
+    if gun != game.player.gun {
+        // Melt unspent rounds back down into whole rocks
+        if let Some(old) = game.player.gun {
+            game.resources.rocks += game.player.ammo / old.rounds_per_rock();
+        }
+        game.player.ammo = 0;
+        game.player.gun = gun;
+    }
+    if let Some(gun) = game.player.gun {
+        ui.label(None, &format!("Ammo: {}", game.player.ammo));
+        if widgets::Button::new(format!("Buy {} rounds (1 rock)", gun.rounds_per_rock()).as_str())
+            .ui(ui)
+            && game.resources.rocks >= 1
+        {
+            game.resources.rocks -= 1;
+            game.player.ammo += gun.rounds_per_rock();
+        }
+    }
+
+    ui.label(None, "Melee");
+    if widgets::Button::new("Bare paws")
+        .selected(game.player.melee.is_none())
+        .ui(ui)
+    {
+        game.player.melee = None;
+    }
+    for (name, melee) in [
+        ("Halberd", Melee::Halberd),
+        ("Screw", Melee::Screw),
+        ("Lightsaber", Melee::Lightsaber),
+    ] {
+        if !game.tech_tree.have(name) {
+            continue;
+        }
+        ui.same_line(0.0);
+        if widgets::Button::new(name)
+            .selected(game.player.melee == Some(melee))
+            .ui(ui)
+        {
+            game.player.melee = Some(melee);
+        }
+    }
+
+    ui.label(None, "Brakes");
+    if widgets::Button::new("No brakes")
+        .selected(game.player.brakes.is_none())
+        .ui(ui)
+    {
+        game.player.brakes = None;
+    }
+    for (name, brakes) in [
+        ("Parachute", Brakes::Parachute),
+        ("Retro Rocket", Brakes::Rocket),
+    ] {
+        if !game.tech_tree.have(name) {
+            continue;
+        }
+        ui.same_line(0.0);
+        if widgets::Button::new(name)
+            .selected(game.player.brakes == Some(brakes))
+            .ui(ui)
+        {
+            game.player.brakes = Some(brakes);
+        }
+    }
+
+    ui.label(None, "Thruster");
+    if widgets::Button::new("No thruster")
+        .selected(game.player.thrusters.is_none())
+        .ui(ui)
+    {
+        game.player.thrusters = None;
+        game.player.load_beans(0);
+    }
+    for (name, thruster) in [
+        ("Bean Thruster", Thruster::Bean),
+        ("Impulse Drive", Thruster::Impulse),
+    ] {
+        if !game.tech_tree.have(name) {
+            continue;
+        }
+        ui.same_line(0.0);
+        if widgets::Button::new(name)
+            .selected(game.player.thrusters == Some(thruster))
+            .ui(ui)
+        {
+            game.player.thrusters = Some(thruster);
+        }
+    }
+    if game.player.thrusters.is_some() {
+        let mut beans = game.player.beans as f32;
+        // Empty stockpile leaves nothing to slide over
+        match game.resources.beans {
+            0 => ui.label(None, "No beans in stock"),
+            stock => {
+                widgets::Slider::new(hash!(), 0.0..stock as f32)
+                    .label("Beans")
+                    .ui(ui, &mut beans);
+            }
+        }
+        let beans = (beans.round() as u32).min(game.resources.beans);
+        if beans != game.player.beans {
+            game.player.load_beans(beans);
+        }
+        ui.label(None, &format!("Delta-v: {:.0} m/s", game.player.delta_v()));
+    }
+}
+
 fn print_multiline(ui: &mut Ui, text: &str, width: f32) {
     let mut acc: f32 = 0.0;
     ui.label(None, ""); // start at new line
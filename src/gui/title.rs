@@ -114,10 +114,11 @@ pub fn title(assets: &SceneAssets, mut state: TitleState, game: &mut Game) -> Sc
                         ui.label(None, text);
                     }
 
-                    if widgets::Button::new("START").size(MENU_BUTTON_SIZE).ui(ui)
-                        && state.scale.is_some()
-                    {
-                        game.settings.scale = match state.scale.unwrap() {
+                    if let (true, Some(scale)) = (
+                        widgets::Button::new("START").size(MENU_BUTTON_SIZE).ui(ui),
+                        state.scale,
+                    ) {
+                        game.settings.scale = match scale {
                             UniverseScale::Small => 0.01,
                             UniverseScale::Medium => 0.1,
                             UniverseScale::Large => 1.0,
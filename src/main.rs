@@ -16,6 +16,16 @@ mod utils;
 pub enum GameError {
     MQError(macroquad::Error),
     NSError(nanoserde::DeJsonErr),
+    BPError(trebuchet::blueprint::BlueprintError),
+}
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::MQError(error) => write!(f, "{error}"),
+            GameError::NSError(error) => write!(f, "{error}"),
+            GameError::BPError(error) => write!(f, "{error}"),
+        }
+    }
 }
 impl From<macroquad::Error> for GameError {
     fn from(error: macroquad::Error) -> GameError {
@@ -27,6 +37,11 @@ impl From<nanoserde::DeJsonErr> for GameError {
         GameError::NSError(error)
     }
 }
+impl From<trebuchet::blueprint::BlueprintError> for GameError {
+    fn from(error: trebuchet::blueprint::BlueprintError) -> GameError {
+        GameError::BPError(error)
+    }
+}
 
 fn window_conf() -> Conf {
     Conf {
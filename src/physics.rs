@@ -1,9 +1,85 @@
-use crate::{utils::*, Game, GameState};
+use crate::{
+    launcher::{Launcher, SiegeEngine},
+    orbit::{OrbitalElements, Trajectory},
+    player::{AMBIENT_TEMPERATURE, PLUSHIE_RADIUS},
+    stats::Outcome,
+    upgrades::{BrakeState, Brakes, Melee},
+    utils::*,
+    world::{body_at, terrain::TerrainClass, World},
+    Game, GameState,
+};
 use macroquad::prelude::*;
-// use std::f32::consts;
+use std::f32::consts;
 
-const PHYSICS_TICK: f32 = 0.001;
+pub mod prediction;
 
+pub const PHYSICS_TICK: f32 = 0.001;
+// Anything slower into the ground than this is resting contact rather than a bounce
+const BOUNCE_SPEED: f32 = 1.0;
+const REST_SPEED: f32 = 0.05;
+// Plushie is put back this far above the surface after touching it
+const CONTACT_LIFT: f32 = 0.005;
+const ROCKY_JAG: f32 = 0.3;
+// Halberd plants into the ground and vaults off at this angle, keeping most of the speed
+const VAULT_SPEED: f32 = 5.0;
+const VAULT_ANGLE: f32 = consts::FRAC_PI_4;
+const VAULT_KEEP: f32 = 0.9;
+// The pole is only good for so many vaults before it bends
+const MAX_VAULTS: u32 = 3;
+// Screw digs along this far under the surface
+const BURROW_DEPTH: f32 = 1.0;
+// Rounds are dropped from the simulation after this many seconds
+const SHOT_LIFETIME: f32 = 5.0;
+// Flailing limbs is all the plushie has to turn itself with, rad/s^2
+const TURN_ACCELERATION: f32 = 20.0;
+const PLUSHIE_VOLUME: f32 = 4.0 / 3.0 * consts::PI * PLUSHIE_RADIUS * PLUSHIE_RADIUS * PLUSHIE_RADIUS;
+const WATER_DENSITY: f32 = 1000.0;
+// Waves and sloshing, lets the bobbing die down
+const WATER_DAMPING: f32 = 2.0;
+// Floating slower than this counts as settled
+const FLOAT_SPEED: f32 = 0.2;
+// Stone skipping needs speed and a glancing angle below the surface
+const SKIP_SPEED: f32 = 8.0;
+const SKIP_ANGLE: f32 = 0.35;
+const SKIP_KEEP: f32 = 0.8;
+const SKIP_BOUNCE: f32 = 0.5;
+/// Time warp multipliers selectable during flight
+pub const WARP_LEVELS: [u32; 5] = [1, 5, 25, 100, 1000];
+// Clearance above the ground each warp level needs, lower levels take over on the way down
+const WARP_CLEARANCE: [f32; 5] = [0.0, 100.0, 1000.0, 10_000.0, 100_000.0];
+// Most ticks run in one frame, a slow frame drops the rest instead of falling further behind
+const MAX_FRAME_TICKS: usize = 20_000;
+
+/// Player controls held during a tick, decoupled from the keyboard so flights can be replayed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Input {
+    pub forward:  bool,
+    pub backward: bool,
+    pub left:     bool,
+    pub right:    bool,
+    pub brake:    bool,
+    pub fire:     bool,
+}
+
+impl Input {
+    pub fn from_keys() -> Input {
+        Input {
+            forward:  is_key_down(KeyCode::W),
+            backward: is_key_down(KeyCode::S),
+            left:     is_key_down(KeyCode::A),
+            right:    is_key_down(KeyCode::D),
+            brake:    is_key_down(KeyCode::Space),
+            fire:     is_key_down(KeyCode::F),
+        }
+    }
+
+    /// Thruster setting, forward and backward cancel out
+    pub fn throttle(&self) -> f32 {
+        self.forward as i32 as f32 - self.backward as i32 as f32
+    }
+}
+
+#[derive(Default)]
 pub struct Physics {
     time_acc:           f32,
     float_displacement: Vec2,
@@ -11,10 +87,7 @@ pub struct Physics {
 
 impl Physics {
     pub async fn init() -> Physics {
-        Physics {
-            time_acc:           0.0,
-            float_displacement: Vec2::ZERO,
-        }
+        Physics::default()
     }
 
     pub fn update(&mut self, game: &mut Game) {
@@ -26,43 +99,145 @@ impl Physics {
             game.state = GameState::Paused;
         }
 
-        self.time_acc += get_frame_time();
-        while self.time_acc > PHYSICS_TICK {
-            self.time_acc -= PHYSICS_TICK;
+        let input = Input::from_keys();
+        self.time_acc += get_frame_time() * warp_factor(game, input) as f32;
+        let ticks = (self.time_acc / PHYSICS_TICK) as usize;
+        self.time_acc -= ticks as f32 * PHYSICS_TICK;
+        if ticks > MAX_FRAME_TICKS {
+            self.time_acc = 0.0;
+        }
+        self.step(game, ticks.min(MAX_FRAME_TICKS), input);
+    }
 
-            // Basic movement
-            if is_key_down(KeyCode::W) {
-                game.player.acceleration +=
-                    Vec2::from_angle(game.player.rotation) * game.player.move_speed;
+    /// Advances the flight by `ticks` physics ticks with `input` held throughout,
+    /// the rest of the batch shrinks with the warp when the plushie comes down into a lower level
+    pub fn step(&mut self, game: &mut Game, mut ticks: usize, input: Input) {
+        let mut warp = warp_factor(game, input) as usize;
+        let mut tick = 0;
+        while tick < ticks {
+            tick += 1;
+            let now = warp_factor(game, input) as usize;
+            if now < warp {
+                ticks = tick + (ticks - tick) * now / warp;
+                warp = now;
             }
-            if is_key_down(KeyCode::S) {
-                game.player.acceleration -=
-                    Vec2::from_angle(game.player.rotation) * game.player.move_speed;
+
+            // Whatever ended the flight last tick, nothing moves after it
+            if game.state != GameState::Launched {
+                break;
             }
-            if is_key_down(KeyCode::A) {
-                game.player.rotation += 0.001;
+            // Brakes stay packed in the sling
+            if input.brake && game.engine.released() {
+                game.player.deploy_brakes();
             }
-            if is_key_down(KeyCode::D) {
-                game.player.rotation -= 0.001;
+            if input.left {
+                game.player.ang_velocity += TURN_ACCELERATION * PHYSICS_TICK;
+            }
+            if input.right {
+                game.player.ang_velocity -= TURN_ACCELERATION * PHYSICS_TICK;
             }
 
-            if !game.trebuchet.run(PHYSICS_TICK) {
-                game.player.position = game.trebuchet.projectile_position();
-                game.player.velocity = game.trebuchet.v_projectile();
-                game.player.rotation =
-                    (game.trebuchet.sling_point() - game.trebuchet.armsling_point()).to_angle();
+            if !game.engine.released() {
+                game.settle_wheels();
+            }
+            let released = game.engine.advance(PHYSICS_TICK);
+            if let Some(breakage) = game.engine.breakage() {
+                // Snapped parts let the plushie fall from wherever the sling had it
+                if game.stats.breakage.is_none() {
+                    game.player.velocity = Vec2::ZERO;
+                    game.player.ang_velocity = 0.0;
+                }
+                game.stats.breakage = Some(breakage);
+            }
+            if let SiegeEngine::Trebuchet(trebuchet) = &game.engine {
+                let projectile = match released {
+                    true => game.player.position,
+                    false => trebuchet.projectile_position(),
+                };
+                game.replay.record(trebuchet.frame(projectile), released);
+            }
+            if !released {
+                game.player.position = game.engine.projectile_position();
+                game.player.velocity = game.engine.projectile_velocity();
+                // Spin of the sling carries over at release
+                let rotation = game.engine.projectile_rotation();
+                let turned = (rotation - game.player.rotation + consts::PI).rem_euclid(consts::TAU)
+                    - consts::PI;
+                game.player.ang_velocity = turned / PHYSICS_TICK;
+                game.player.rotation = rotation;
                 continue;
             }
 
-            game.player.acceleration += game.world.grativy_at(game.player.position);
+            if game.player.burrow != 0.0 {
+                if dig(game, PHYSICS_TICK) {
+                    game.state = GameState::Landed;
+                    break;
+                }
+                continue;
+            }
+
+            if let Some(shot) = game.player.fire(input.fire, PHYSICS_TICK) {
+                game.shots.push(shot);
+                game.stats.shots += 1;
+            }
+            for shot in game.shots.iter_mut() {
+                let body = body_at(&game.world, &game.moons, shot.position);
+                shot.velocity += body.grativy_at(shot.position) * PHYSICS_TICK;
+                shot.position += to_i64coords(shot.velocity * PHYSICS_TICK);
+                shot.age += PHYSICS_TICK;
+            }
+            let (world, moons) = (&game.world, &game.moons);
+            game.shots.retain(|shot| {
+                shot.age < SHOT_LIFETIME
+                    && body_at(world, moons, shot.position).clearance(shot.position) > 0.0
+            });
+
+            // Patched gravity, only the body whose sphere of influence the plushie is in pulls on it
+            let body = body_at(&game.world, &game.moons, game.player.position);
+            let gravity = body.grativy_at(game.player.position);
+            game.player.acceleration += gravity;
+            // Drag and buoyancy held constant over the tick, velocity barely changes in 1ms
+            let density = body.density_at(game.player.position);
+            let depth = body.water_depth(game.player.position);
+            let submerged = (depth / (2.0 * PLUSHIE_RADIUS)).min(1.0);
+            let buoyancy = -gravity * WATER_DENSITY * PLUSHIE_VOLUME * submerged / game.player.mass;
+            let thrust = game.player.burn(input.throttle(), PHYSICS_TICK);
+            let brake = game.player.brake(density, PHYSICS_TICK);
+            let forces = game.player.drag(density) / game.player.mass + buoyancy + thrust + brake;
+            game.player.acceleration += forces;
+            game.player.ang_velocity +=
+                game.player.torque(density) / game.player.moment_of_inertia() * PHYSICS_TICK;
+            game.player.rotation += game.player.ang_velocity * PHYSICS_TICK;
+            // Rolling along the ground is no trick, only turns in the air count as flips
+            let up = to_meters(game.player.position - body.position).normalize();
+            let below = game.player.position - to_i64coords(up * PLUSHIE_RADIUS);
+            match ground_collision(body, game.player.position, below) {
+                Some(_) => game.stats.flip_angle = 0.0,
+                None => game.stats.record_turn(game.player.ang_velocity * PHYSICS_TICK),
+            }
+            match depth > 0.0 {
+                // Sea quenches whatever heat was built up
+                true => game.player.temperature = AMBIENT_TEMPERATURE,
+                false => game.player.update_heat(density, PHYSICS_TICK),
+            }
             let displacement = (game.player.velocity * PHYSICS_TICK)
                 + 0.5 * game.player.acceleration * PHYSICS_TICK.powi(2);
-            self.float_displacement += displacement;
 
-            let next_position = game.player.position + to_i64coords(self.float_displacement);
-            let next_gravity = game.world.grativy_at(next_position);
-            game.player.velocity += 0.5 * (game.player.acceleration + next_gravity) * PHYSICS_TICK;
+            let i64_displacement: I64Vec2;
+            (i64_displacement, self.float_displacement) =
+                to_i64coords_with_rem(self.float_displacement + displacement);
+            let next_position = game.player.position + i64_displacement;
+            let next_gravity = body.grativy_at(next_position);
+            game.player.velocity +=
+                0.5 * (game.player.acceleration + next_gravity + forces) * PHYSICS_TICK;
             game.player.acceleration = Vec2::ZERO;
+            if depth > 0.0 {
+                // Water drag is stiff enough to overshoot explicitly, take it implicitly
+                let speed = game.player.velocity.length();
+                let water_drag = 0.5 * WATER_DENSITY * game.player.drag_area() * submerged
+                    / game.player.mass;
+                game.player.velocity /= 1.0 + (water_drag * speed + WATER_DAMPING) * PHYSICS_TICK;
+            }
 
             game.stats.time += PHYSICS_TICK;
             game.stats.distance += displacement.length();
@@ -71,36 +246,261 @@ impl Physics {
                 .max_altitude
                 .max(game.world.altitude_at(game.player.position));
             game.stats.max_speed = game.stats.max_speed.max(game.player.velocity.length());
+            game.stats.max_temperature = game.stats.max_temperature.max(game.player.temperature);
 
-            if let Some(point) = ground_collision(game, displacement) {
-                game.player.position = point;
+            // Nothing brings the plushie back once it is past the air on a path that misses the ground
+            if body.altitude_at(game.player.position) > body.atmosphere_height() {
+                let outcome = coasting(body, &game.moons, game.player.position, game.player.velocity);
+                if outcome.is_some() {
+                    game.stats.outcome = outcome;
+                    game.state = GameState::Landed;
+                    self.float_displacement = Vec2::ZERO;
+                    break;
+                }
+            }
+
+            if game.player.overheated() {
+                game.stats.outcome = Some(Outcome::BurnedUp);
                 game.player.velocity = Vec2::ZERO;
                 game.state = GameState::Landed;
                 self.float_displacement = Vec2::ZERO;
                 break;
             }
+
+            if depth == 0.0 && body.water_depth(next_position) > 0.0 {
+                let up = to_meters(game.player.position - body.position).normalize();
+                if skip(game, up) {
+                    self.float_displacement = Vec2::ZERO;
+                    continue;
+                }
+                game.stats.outcome.get_or_insert(Outcome::Splashdown);
+                game.stats.landing_speed.get_or_insert(game.player.velocity.length());
+            }
+            if depth > 0.0
+                && depth < 2.0 * PLUSHIE_RADIUS
+                && game.player.velocity.length() < FLOAT_SPEED
+            {
+                game.player.velocity = Vec2::ZERO;
+                game.state = GameState::Landed;
+                break;
+            }
+
+            let body = game.body_at(game.player.position);
+            let on_moon = body.soi.is_finite();
+            let Some(contact) = ground_collision(body, game.player.position, next_position) else {
+                game.player.position = next_position;
+                continue;
+            };
+            game.player.position = contact.point + to_i64coords(contact.normal * CONTACT_LIFT);
+            self.float_displacement = Vec2::ZERO;
+            game.stats.landing_speed.get_or_insert(game.player.velocity.length());
+            game.stats.flip_angle = 0.0;
+            let melee = game.player.melee;
+            let rest = match melee {
+                Some(Melee::Halberd) if vault(game, &contact) => false,
+                Some(Melee::Screw) if start_burrow(game, &contact) => false,
+                _ => bounce(game, &contact),
+            };
+            if rest {
+                if on_moon {
+                    game.stats.outcome.get_or_insert(Outcome::MoonLanding);
+                }
+                game.player.velocity = Vec2::ZERO;
+                game.state = GameState::Landed;
+                break;
+            }
         }
+    }
+}
+
+/// Warp actually applied, held at 1x while firing, thrusting or retro burning and capped near the ground
+pub fn warp_factor(game: &Game, input: Input) -> u32 {
+    let thrusting = input.throttle() != 0.0 && game.player.can_burn();
+    let retro = game.player.brakes == Some(Brakes::Rocket)
+        && game.player.brake_state == BrakeState::Deployed;
+    if !game.engine.released() || thrusting || retro || (input.fire && game.player.can_fire()) {
+        return 1;
+    }
+    let clearance = game.body_at(game.player.position).clearance(game.player.position);
+    let level = (0..=game.warp.min(WARP_LEVELS.len() - 1))
+        .rev()
+        .find(|level| clearance >= WARP_CLEARANCE[*level])
+        .unwrap_or(0);
+    WARP_LEVELS[level]
+}
 
-        let i64_displacement: I64Vec2;
-        (i64_displacement, self.float_displacement) =
-            to_i64coords_with_rem(self.float_displacement);
-        game.player.position += i64_displacement;
+/// Outcome once coasting above the air around `body` on a path that never comes back down
+fn coasting(body: &World, moons: &[World], position: I64Vec2, velocity: Vec2) -> Option<Outcome> {
+    let orbit = OrbitalElements::around(body, position, velocity);
+    // Apoapsis beyond here swings into some sphere of influence, moons have theirs as the limit
+    let reach = match body.soi.is_finite() {
+        true => body.soi,
+        false => moons
+            .iter()
+            .map(|moon| (moon.position - body.position).to_meters().length() - moon.soi)
+            .fold(f32::INFINITY, f32::min),
+    };
+    match (orbit.trajectory(body), orbit.apoapsis) {
+        (Trajectory::Orbit, Some(apoapsis)) if apoapsis < reach => Some(Outcome::Orbit),
+        // Leaving a moon only hands the plushie back to the planet
+        (Trajectory::Escape, _) if !body.soi.is_finite() => Some(Outcome::Escape),
+        _ => None,
     }
 }
 
-fn ground_collision(game: &Game, displacement: Vec2) -> Option<I64Vec2> {
-    let circ = game.world.height_map.len();
-    let terrain_index = game.world.terrain_index_beneath(game.player.position);
-    let terrain_a = game.world.surface(terrain_index);
-    let terrain_b = game.world.surface((terrain_index + 1) % circ);
+/// Skips off the sea surface if coming in fast and flat enough, `up` points away from the body
+fn skip(game: &mut Game, up: Vec2) -> bool {
+    let player = &mut game.player;
+    let down = -player.velocity.dot(up);
+    let along = player.velocity + down * up;
+    if player.velocity.length() < SKIP_SPEED || down > along.length() * SKIP_ANGLE.tan() {
+        return false;
+    }
+    player.velocity = along * SKIP_KEEP + down * SKIP_BOUNCE * up;
+    game.stats.skips += 1;
+    true
+}
 
-    let next_position = game.player.position + to_i64coords(displacement);
+struct Contact {
+    point:  I64Vec2,
+    normal: Vec2,
+    class:  TerrainClass,
+}
 
-    // If player over terrain at next position
-    if orientation(terrain_a, terrain_b, next_position) == Orientation::Clockwise {
-        return None;
+/// Earliest crossing into the ground between `position` and `next_position`
+///
+/// Walks every height map segment the step passes over, going the short way around the seam,
+/// so fast plushies can't tunnel through hills between ticks
+fn ground_collision(world: &World, position: I64Vec2, next_position: I64Vec2) -> Option<Contact> {
+    let circ = world.height_map.len();
+    let start = world.terrain_index_beneath(position);
+    let end = world.terrain_index_beneath(next_position);
+    let ahead = (end + circ - start) % circ;
+    let behind = (start + circ - end) % circ;
+    let (count, stride) = match ahead <= behind {
+        true => (ahead, 1),
+        false => (behind, circ - 1),
+    };
+
+    (0..=count)
+        .map(|k| (start + k * stride) % circ)
+        .find_map(|terrain_index| {
+            let terrain_a = world.surface(terrain_index);
+            let terrain_b = world.surface((terrain_index + 1) % circ);
+
+            // If player over terrain at next position
+            if orientation(terrain_a, terrain_b, next_position) == Orientation::Clockwise {
+                return None;
+            }
+            // Starting under the segment below is resting contact, anywhere else the step has to
+            // actually pass through the segment between its ends
+            let above = orientation(terrain_a, terrain_b, position) == Orientation::Clockwise;
+            let resting = terrain_index == start && !above;
+            let crosses = above
+                && orientation(position, next_position, terrain_a)
+                    != orientation(position, next_position, terrain_b);
+            if !resting && !crosses {
+                return None;
+            }
+            let point = get_intersection(terrain_a, terrain_b, position, next_position)?;
+            Some(Contact {
+                point,
+                normal: world.surface_normal(point),
+                class: world.terrain_class(terrain_index),
+            })
+        })
+}
+
+/// Pole-vaults off the ground with the halberd, false if too slow or the pole is worn out
+fn vault(game: &mut Game, contact: &Contact) -> bool {
+    let player = &mut game.player;
+    let into = player.velocity.dot(contact.normal);
+    let tangent = player.velocity - into * contact.normal;
+    if into >= 0.0 || tangent.length() < VAULT_SPEED || game.stats.vaults >= MAX_VAULTS {
+        return false;
+    }
+    let speed = player.velocity.length() * VAULT_KEEP;
+    player.velocity = (tangent.normalize() * VAULT_ANGLE.cos() + contact.normal * VAULT_ANGLE.sin()) * speed;
+    game.stats.vaults += 1;
+    true
+}
+
+/// Screws into the ground with whatever speed it had along the surface, false if too slow to dig
+fn start_burrow(game: &mut Game, contact: &Contact) -> bool {
+    let body = game.body_at(contact.point);
+    let anticlockwise = to_meters(contact.point - body.position).normalize().perp();
+    let burrow = game.player.velocity.dot(anticlockwise);
+    if burrow.abs() < REST_SPEED {
+        return false;
     }
-    get_intersection(terrain_a, terrain_b, game.player.position, next_position)
+    game.player.burrow = burrow;
+    game.player.velocity = Vec2::ZERO;
+    true
+}
+
+/// Digs along under the surface for `dt`, true once the screw has stopped
+fn dig(game: &mut Game, dt: f32) -> bool {
+    let body = body_at(&game.world, &game.moons, game.player.position);
+    let index = body.terrain_index_beneath(game.player.position);
+    let drag = body.terrain_class(index).burrow_drag() * dt;
+    let player = &mut game.player;
+    let burrow = (player.burrow.abs() - drag).max(0.0).copysign(player.burrow);
+
+    let offset = (player.position - body.position).as_dvec2();
+    let angle = offset.y.atan2(offset.x) + (burrow * dt / body.radius) as f64;
+    player.position = body.point_under_surface(angle, BURROW_DEPTH);
+    player.burrow = burrow;
+    game.stats.time += dt;
+    game.stats.distance += burrow.abs() * dt;
+    game.stats.burrowed += burrow.abs() * dt;
+    burrow == 0.0
+}
+
+/// Bounces off or slides along the ground, true once the plushie has come to rest
+fn bounce(game: &mut Game, contact: &Contact) -> bool {
+    let player = &mut game.player;
+    // Lightsaber slices the jags and snags off rocks instead of catching on them
+    let slicing = player.melee == Some(Melee::Lightsaber) && contact.class == TerrainClass::Rocky;
+    let into = player.velocity.dot(contact.normal);
+    if into >= 0.0 {
+        return false;
+    }
+
+    let bouncing = -into > BOUNCE_SPEED;
+    let normal = match (bouncing, contact.class) {
+        // Jagged rocks kick off at odd angles, fixed per spot so replays match
+        (true, TerrainClass::Rocky) if !slicing => {
+            let jag = (contact.point.x ^ contact.point.y).rem_euclid(64) as f32 / 32.0 - 1.0;
+            Vec2::from_angle(jag * ROCKY_JAG).rotate(contact.normal)
+        }
+        _ => contact.normal,
+    };
+    let restitution = match bouncing {
+        true => contact.class.restitution(),
+        false => 0.0,
+    };
+
+    let into = player.velocity.dot(normal).min(0.0);
+    let tangent = player.velocity - into * normal;
+    // Friction takes out speed along the ground in proportion to the push into it
+    let impulse = -(1.0 + restitution) * into;
+    let friction = match slicing {
+        true => 0.0,
+        false => contact.class.friction(),
+    };
+    let slide = (tangent.length() - friction * impulse).max(0.0);
+    player.velocity = tangent.normalize_or_zero() * slide - restitution * into * normal;
+
+    // Rolls along with the ground
+    player.ang_velocity = normal.perp_dot(player.velocity) / PLUSHIE_RADIUS;
+
+    if bouncing {
+        game.stats.bounces += 1;
+        if slicing {
+            game.stats.rocks_cut += 1;
+        }
+    }
+    !bouncing && player.velocity.length() < REST_SPEED
 }
 
 #[derive(Debug, PartialEq)]
@@ -126,11 +526,410 @@ fn to_i64coords_with_rem(f32coords: Vec2) -> (I64Vec2, Vec2) {
     (i64coords, remainder)
 }
 
+/// Setup shared by the flight tests here and in `prediction`
+#[cfg(test)]
+mod flight_setup {
+    use super::{Input, Physics};
+    use crate::{
+        player::Player,
+        utils::*,
+        world::{terrain::TerrainClass, World, WorldClass},
+        Game, GameState,
+    };
+    use macroquad::math::I64Vec2;
+
+    /// New game on the loaded launch pad
+    pub(super) fn fresh_game() -> Game {
+        let mut game = Game::headless();
+        game.new_game();
+        game
+    }
+
+    /// Fresh game let go and flown for `ticks` with no input
+    pub(super) fn launched_game(ticks: usize) -> Game {
+        fly(&fresh_game(), &[ticks], Input::default())
+    }
+
+    /// Flies from a copy of `start`, terrain noise shares the global rand state with other tests
+    pub(super) fn fly(start: &Game, chunks: &[usize], input: Input) -> Game {
+        let mut game = Game::headless();
+        game.world = start.world.clone();
+        game.engine = start.engine.clone();
+        game.player = Player::new(start.player.position);
+        game.player.thrusters = start.player.thrusters;
+        game.player.load_beans(start.player.beans);
+        game.state = GameState::Launched;
+        let mut physics = Physics::default();
+        for ticks in chunks {
+            physics.step(&mut game, *ticks, input);
+        }
+        game
+    }
+
+    /// Earth sized world of one terrain all the way round
+    pub(super) fn world_of(class: TerrainClass) -> World {
+        World::new(
+            0.01,
+            I64Vec2::ZERO,
+            6_371_000.0,
+            5.972e+24,
+            WorldClass::Minshara,
+            Some(&[(class, 40_000)]),
+        )
+    }
+
+    /// Point `height` meters above the surface vertex at `index`
+    pub(super) fn above(world: &World, index: usize, height: f32) -> I64Vec2 {
+        let surface = world.surface(index);
+        surface + to_i64coords(to_meters(surface - world.position).normalize() * height)
+    }
+}
+
 #[cfg(test)]
 mod physics_test {
-    use super::to_i64coords_with_rem;
+    use super::{
+        bounce, dig, flight_setup::*, ground_collision, start_burrow, to_i64coords_with_rem, vault,
+        warp_factor, Contact, Input, Physics, CONTACT_LIFT, PLUSHIE_RADIUS, WARP_LEVELS,
+    };
+    use crate::{
+        launcher::{Launcher, SiegeEngine},
+        trebuchet::{Trebuchet, TrebuchetArm, TrebuchetMaterial, TrebuchetWeight},
+        stats::Outcome,
+        upgrades::{BrakeState, Brakes, Melee, Thruster},
+        utils::*,
+        world::terrain::TerrainClass,
+        Game, GameState,
+    };
     use macroquad::math::{I64Vec2, Vec2};
 
+    #[test]
+    fn deterministic() {
+        let input = Input {
+            forward: true,
+            left: true,
+            ..Default::default()
+        };
+        let mut start = fresh_game();
+        start.player.thrusters = Some(Thruster::Bean);
+        start.player.load_beans(100);
+        let whole = fly(&start, &[3000], input);
+        let split = fly(&start, &[1000, 1500, 500], input);
+        assert_eq!(whole.player.position, split.player.position);
+        assert_eq!(
+            whole.player.velocity.to_array().map(f32::to_bits),
+            split.player.velocity.to_array().map(f32::to_bits)
+        );
+        assert_eq!(whole.stats.distance.to_bits(), split.stats.distance.to_bits());
+        assert_eq!(whole.stats.max_speed.to_bits(), split.stats.max_speed.to_bits());
+
+        let idle = fly(&start, &[3000], Input::default());
+        assert_ne!(whole.player.position, idle.player.position);
+    }
+
+    #[test]
+    fn brakes_after_release() {
+        let mut game = launched_game(0);
+        game.player.brakes = Some(Brakes::Rocket);
+        let brake = Input { brake: true, ..Default::default() };
+        let mut physics = Physics::default();
+        while !game.engine.released() {
+            assert_eq!(game.player.brake_state, BrakeState::Stowed);
+            physics.step(&mut game, 1, brake);
+        }
+        physics.step(&mut game, 1, brake);
+        assert_eq!(game.player.brake_state, BrakeState::Deployed);
+    }
+
+    #[test]
+    fn broken_launch() {
+        let mut game = launched_game(0);
+        let material = TrebuchetMaterial::Cardboard;
+        let trebuchet = Trebuchet::init(game.engine.position())
+            .base(1.0, material)
+            .arm(TrebuchetArm::new(1.6, 0.4, material))
+            .weight(TrebuchetWeight::new(0.3, 200.0, material))
+            .build();
+        game.engine = SiegeEngine::Trebuchet(trebuchet);
+        game.engine.reset();
+        game.player.position = game.engine.projectile_position();
+
+        let mut physics = Physics::default();
+        while game.stats.breakage.is_none() {
+            physics.step(&mut game, 1, Input::default());
+        }
+        assert!(game.player.velocity.length() < 0.1, "{:?}", game.player.velocity);
+        assert_eq!(game.stats.crunch(), 0);
+
+        // Plushie is loose, so the brakes open and warp picks up once it is clear of the ground
+        assert!(game.engine.released());
+        game.player.brakes = Some(Brakes::Parachute);
+        physics.step(&mut game, 1, Input { brake: true, ..Default::default() });
+        assert_eq!(game.player.brake_state, BrakeState::Deployed);
+        game.warp = WARP_LEVELS.len() - 1;
+        let up = to_meters(game.player.position - game.world.position).normalize();
+        game.player.position += to_i64coords(up * 500.0);
+        assert!(warp_factor(&game, Input::default()) > 1);
+    }
+
+    #[test]
+    fn moon_landing() {
+        let mut game = launched_game(3000);
+        let moon = &game.moons[0];
+        let up = to_meters(moon.surface(100) - moon.position).normalize();
+        game.player.position = moon.surface(100) + to_i64coords(up * 2.0);
+        game.player.velocity = Vec2::ZERO;
+
+        Physics::default().step(&mut game, 10_000, Input::default());
+        assert_eq!(game.state, GameState::Landed);
+        assert_eq!(game.stats.outcome, Some(Outcome::MoonLanding));
+
+        // Ticks left in the batch after landing do nothing
+        let (position, time) = (game.player.position, game.stats.time);
+        Physics::default().step(&mut game, 1000, Input { forward: true, ..Default::default() });
+        assert_eq!(game.player.position, position);
+        assert_eq!(game.stats.time, time);
+    }
+
+    #[test]
+    fn swept_collision() {
+        let world = world_of(TerrainClass::Plain);
+        let circ = world.height_map.len();
+
+        // Orbital speed and more, several segments in one tick
+        let position = above(&world, 10, 2000.0);
+        let next_position = above(&world, 14, -100.0);
+        let contact = ground_collision(&world, position, next_position).expect("hits the ground");
+        let hit = world.terrain_index_beneath(contact.point);
+        assert!((10..14).contains(&hit), "hit at {hit}");
+        assert!(contact.normal.dot(to_meters(contact.point - world.position)) > 0.0);
+
+        // Skimming chord dips under the hills and back out within the step
+        let position = above(&world, 100, 1.0);
+        let next_position = above(&world, 110, 1.0);
+        let contact = ground_collision(&world, position, next_position).expect("no tunneling");
+        assert!(to_meters(contact.point - position).length() < 2000.0);
+        let miss = ground_collision(&world, above(&world, 100, 300.0), above(&world, 110, 300.0));
+        assert!(miss.is_none());
+
+        // Either way across the wrap-around seam
+        for (from, to) in [(circ - 2, 1), (1, circ - 2)] {
+            let contact = ground_collision(&world, above(&world, from, 50.0), above(&world, to, -50.0))
+                .expect("hits across the seam");
+            let hit = world.terrain_index_beneath(contact.point);
+            assert!(hit >= circ - 2 || hit <= 1, "hit at {hit}");
+        }
+    }
+
+    #[test]
+    fn flips() {
+        let mut game = launched_game(3000);
+        game.world = world_of(TerrainClass::Plain);
+        game.state = GameState::Launched;
+        game.stats = Default::default();
+        let up = to_meters(game.world.surface(5) - game.world.position).normalize();
+
+        // Rolling along the ground turns the plushie plenty but is no trick
+        game.player.position = above(&game.world, 5, CONTACT_LIFT);
+        game.player.velocity = up.perp() * 20.0;
+        Physics::default().step(&mut game, 2000, Input::default());
+        assert!(game.stats.distance > 10.0, "rolled {}", game.stats.distance);
+        assert_eq!(game.stats.flips, 0);
+
+        // Same spin in the air does count
+        game.state = GameState::Launched;
+        game.player.position = above(&game.world, 5, 1000.0);
+        game.player.velocity = Vec2::ZERO;
+        game.player.ang_velocity = 20.0;
+        Physics::default().step(&mut game, 1000, Input::default());
+        assert!(game.stats.flips > 0);
+    }
+
+    /// Launched game with the plushie falling at `speed` onto ground of `class` from a meter up
+    fn drop_onto(class: TerrainClass, speed: f32) -> Game {
+        let mut game = launched_game(3000);
+        game.world = world_of(class);
+        game.state = GameState::Launched;
+        game.stats = Default::default();
+        game.player.position = above(&game.world, 5, 1.0);
+        let up = to_meters(game.player.position - game.world.position).normalize();
+        game.player.velocity = up.perp() * 2.0 - up * speed;
+        game
+    }
+
+    /// Ticks until the plushie comes to rest, checking it never does so while still moving
+    fn settle(game: &mut Game) {
+        let mut physics = Physics::default();
+        let mut ticks = 0;
+        while game.state == GameState::Launched {
+            let speed = game.player.velocity.length();
+            physics.step(game, 1, Input::default());
+            ticks += 1;
+            if game.state == GameState::Landed {
+                assert!(speed < 1.0, "landed at {speed} m/s");
+            }
+            assert!(ticks < 60_000, "never settled");
+        }
+        assert_eq!(game.player.velocity, Vec2::ZERO);
+    }
+
+    #[test]
+    fn bounces() {
+        let mut plain = drop_onto(TerrainClass::Plain, 15.0);
+        let mut physics = Physics::default();
+        while plain.stats.bounces == 0 {
+            physics.step(&mut plain, 1, Input::default());
+        }
+        // Springs back up rather than stopping on the spot
+        let up = to_meters(plain.player.position - plain.world.position).normalize();
+        assert!(plain.player.velocity.dot(up) > 5.0);
+        assert_eq!(plain.state, GameState::Launched);
+        settle(&mut plain);
+        assert!(plain.stats.bounces > 2, "{} bounces", plain.stats.bounces);
+
+        // Sand soaks the landing up within a few contacts
+        let mut sands = drop_onto(TerrainClass::Sands, 15.0);
+        settle(&mut sands);
+        assert!(sands.stats.bounces <= 2, "{} bounces", sands.stats.bounces);
+        assert!(sands.stats.time < plain.stats.time);
+    }
+
+    /// Launched game over open sea, `height` meters above sea level
+    /// and moving at `velocity` along the surface and up
+    fn at_sea(height: f32, velocity: Vec2) -> Game {
+        let mut game = launched_game(3000);
+        game.world = world_of(TerrainClass::Ocean);
+        game.state = GameState::Launched;
+        game.stats = Default::default();
+        game.player.position = game.world.point_from_sealevel(200, height);
+        let up = to_meters(game.player.position - game.world.position).normalize();
+        game.player.velocity = up.perp() * velocity.x + up * velocity.y;
+        game
+    }
+
+    #[test]
+    fn stone_skip() {
+        let mut game = at_sea(0.5, Vec2::new(30.0, -2.0));
+        Physics::default().step(&mut game, 500, Input::default());
+        assert!(game.stats.skips > 0);
+        assert_eq!(game.stats.outcome, None);
+        assert_eq!(game.state, GameState::Launched);
+    }
+
+    #[test]
+    fn splashdown() {
+        let mut game = at_sea(0.5, Vec2::new(5.0, -20.0));
+        Physics::default().step(&mut game, 500, Input::default());
+        assert_eq!(game.stats.skips, 0);
+        assert_eq!(game.stats.outcome, Some(Outcome::Splashdown));
+        assert!(game.world.water_depth(game.player.position) > 0.0);
+    }
+
+    #[test]
+    fn floats_up() {
+        let mut game = at_sea(-5.0, Vec2::ZERO);
+        let mut physics = Physics::default();
+        let mut ticks = 0;
+        while game.state == GameState::Launched {
+            physics.step(&mut game, 1, Input::default());
+            ticks += 1;
+            assert!(ticks < 60_000, "still bobbing");
+        }
+        let depth = game.world.water_depth(game.player.position);
+        assert!(depth > 0.0 && depth < 2.0 * PLUSHIE_RADIUS, "settled {depth}m under");
+        assert_eq!(game.player.velocity, Vec2::ZERO);
+    }
+
+    #[test]
+    fn melee() {
+        let mut game = Game::headless();
+        game.world = world_of(TerrainClass::Plain);
+        let up = to_meters(game.world.surface(5) - game.world.position).normalize();
+        let contact = |class| Contact {
+            point: game.world.surface(5),
+            normal: up,
+            class,
+        };
+        let plain = contact(TerrainClass::Plain);
+        let rocky = contact(TerrainClass::Rocky);
+        let incoming = up.perp() * 20.0 - up * 5.0;
+
+        // Halberd trades the dive for a climb
+        game.player.melee = Some(Melee::Halberd);
+        game.player.velocity = incoming;
+        assert!(vault(&mut game, &plain));
+        assert!(game.player.velocity.dot(up) > 10.0);
+        assert_eq!(game.stats.vaults, 1);
+
+        // Lightsaber goes through rocks without snagging
+        game.player.melee = Some(Melee::Lightsaber);
+        game.player.velocity = incoming;
+        bounce(&mut game, &rocky);
+        assert_eq!(game.player.velocity.dot(up.perp()), 20.0);
+        assert_eq!(game.stats.rocks_cut, 1);
+        game.player.melee = None;
+        game.player.velocity = incoming;
+        bounce(&mut game, &rocky);
+        assert!(game.player.velocity.dot(up.perp()) < 19.0);
+
+        // Screw digs until the ground's drag stops it
+        game.player.melee = Some(Melee::Screw);
+        game.player.position = plain.point;
+        game.player.velocity = incoming;
+        assert!(start_burrow(&mut game, &plain));
+        let mut ticks = 0;
+        while !dig(&mut game, 0.001) {
+            ticks += 1;
+            assert!(game.world.clearance(game.player.position) < 0.0);
+        }
+        let expected = 20.0_f32.powi(2) / (2.0 * TerrainClass::Plain.burrow_drag());
+        assert!((game.stats.burrowed - expected).abs() < 0.5, "{}", game.stats.burrowed);
+        assert!(ticks > 1000);
+        // Digging still counts as flight
+        assert_eq!(game.stats.distance, game.stats.burrowed);
+        assert!((game.stats.time - (ticks + 1) as f32 * 0.001).abs() < 0.01);
+        let moved = to_meters(game.player.position - plain.point).dot(up.perp());
+        assert!((moved - expected).abs() < 1.0, "moved {moved}");
+    }
+
+    #[test]
+    fn warp_limits() {
+        let mut game = launched_game(0);
+        game.warp = WARP_LEVELS.len() - 1;
+        assert_eq!(warp_factor(&game, Input::default()), 1);
+
+        Physics::default().step(&mut game, 3000, Input::default());
+        assert!(game.engine.released());
+        let up = to_meters(game.player.position - game.world.position).normalize();
+        game.player.position += to_i64coords(up * 500.0);
+        assert_eq!(warp_factor(&game, Input::default()), 5);
+        game.player.position += to_i64coords(up * 500_000.0);
+        assert_eq!(warp_factor(&game, Input::default()), 1000);
+        let thrust = Input {
+            forward: true,
+            ..Default::default()
+        };
+        assert_eq!(warp_factor(&game, thrust), 1000);
+        game.player.thrusters = Some(Thruster::Impulse);
+        game.player.load_beans(10);
+        assert_eq!(warp_factor(&game, thrust), 1);
+        game.player.brakes = Some(Brakes::Rocket);
+        game.player.deploy_brakes();
+        assert_eq!(warp_factor(&game, Input::default()), 1);
+        game.player.pack_brakes();
+        assert_eq!(warp_factor(&game, Input::default()), 1000);
+
+        // Diving through the clearance mid-batch slows the rest of it down
+        let clearance = game.world.clearance(game.player.position);
+        game.player.position += to_i64coords(up * (100_010.0 - clearance));
+        game.player.velocity = -up * 1000.0;
+        assert_eq!(warp_factor(&game, Input::default()), 1000);
+        let time = game.stats.time;
+        Physics::default().step(&mut game, 1000, Input::default());
+        let flown = game.stats.time - time;
+        assert!(flown < 0.2, "flew {flown}s");
+        assert_eq!(warp_factor(&game, Input::default()), 100);
+    }
+
     #[macroquad::test("Test")]
     async fn i64remainder() {
         let mut ami = Vec2::splat(0.5);
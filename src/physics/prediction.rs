@@ -0,0 +1,226 @@
+use super::{ground_collision, to_i64coords_with_rem, PHYSICS_TICK};
+use crate::{
+    launcher::{EngineKind, Launcher, SiegeEngine},
+    player::Player,
+    trebuchet::blueprint::Blueprint,
+    utils::*,
+    world::{body_at, World},
+    Game, GameState,
+};
+use macroquad::prelude::*;
+
+// Coarse steps far from the ground, fine ones close to it
+const STEP_FRACTION: f32 = 0.01;
+const MIN_STEP: Meters = 0.25;
+const MAX_STEP: Meters = 50_000.0;
+// Keeps the time step sane around the top of a lob
+const MIN_SPEED: f32 = 5.0;
+const MAX_STEPS: usize = 5000;
+// Firing sequences longer than this are taken as stalled
+const MAX_RELEASE_TICKS: usize = 20_000;
+
+/// Predicted flight path, ends at the impact point if the ground or sea is reached
+pub struct Prediction {
+    pub points: Vec<I64Vec2>,
+    pub impact: Option<I64Vec2>,
+}
+
+/// What the release depends on, only trebuchets can be redesigned so the other engines go by type
+#[derive(Clone, PartialEq)]
+struct Design {
+    kind:      EngineKind,
+    position:  I64Vec2,
+    blueprint: Option<Blueprint>,
+}
+
+impl Design {
+    fn of(engine: &SiegeEngine) -> Design {
+        Design {
+            kind:      engine.kind(),
+            position:  engine.position(),
+            blueprint: match engine {
+                SiegeEngine::Trebuchet(trebuchet) => Some(Blueprint::from_trebuchet("", trebuchet)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Everything the traced path depends on
+#[derive(Clone, Copy, PartialEq)]
+struct Start {
+    position:  I64Vec2,
+    velocity:  Vec2,
+    drag_area: f32,
+    mass:      f32,
+}
+
+/// Keeps the last prediction, the firing sequence only reruns when the design changes
+/// and the path only when the plushie's state or build does
+#[derive(Default)]
+pub struct Predictor {
+    design:     Option<Design>,
+    release:    Option<(I64Vec2, Vec2)>,
+    start:      Option<Start>,
+    prediction: Option<Prediction>,
+}
+
+impl Predictor {
+    /// Path of the plushie from its current state, or from the engine's release while it is still loaded
+    pub fn update(&mut self, game: &Game) {
+        // Nothing is drawn outside of these, and a new game may bring new terrain
+        if !matches!(game.state, GameState::PreLaunch | GameState::Launched) {
+            *self = Predictor::default();
+            return;
+        }
+
+        let (position, velocity) = match game.engine.released() {
+            true => (game.player.position, game.player.velocity),
+            false => {
+                let design = Design::of(&game.engine);
+                if self.design.as_ref() != Some(&design) {
+                    self.release = release_state(&game.engine);
+                    self.design = Some(design);
+                }
+                match self.release {
+                    Some(release) => release,
+                    None => {
+                        self.start = None;
+                        self.prediction = None;
+                        return;
+                    }
+                }
+            }
+        };
+
+        let start = Start {
+            position,
+            velocity,
+            drag_area: game.player.drag_area(),
+            mass: game.player.mass,
+        };
+        if self.start != Some(start) {
+            self.prediction = Some(trace(&game.world, &game.moons, &game.player, position, velocity));
+            self.start = Some(start);
+        }
+    }
+
+    pub fn prediction(&self) -> Option<&Prediction> {
+        self.prediction.as_ref()
+    }
+}
+
+/// Runs a copy of the engine from rest until it lets go, `None` if it breaks or never does
+fn release_state(engine: &SiegeEngine) -> Option<(I64Vec2, Vec2)> {
+    let mut engine = engine.clone();
+    engine.reset();
+    for _ in 0..MAX_RELEASE_TICKS {
+        let (position, velocity) = (engine.projectile_position(), engine.projectile_velocity());
+        if engine.advance(PHYSICS_TICK) {
+            return match engine.breakage() {
+                Some(_) => None,
+                None => Some((position, velocity)),
+            };
+        }
+    }
+    None
+}
+
+fn trace(
+    planet: &World,
+    moons: &[World],
+    player: &Player,
+    mut position: I64Vec2,
+    mut velocity: Vec2,
+) -> Prediction {
+    let mut points = vec![position];
+    let mut float_displacement = Vec2::ZERO;
+
+    for _ in 0..MAX_STEPS {
+        let world = body_at(planet, moons, position);
+        let step = (world.clearance(position) * STEP_FRACTION).clamp(MIN_STEP, MAX_STEP);
+        let dt = step / velocity.length().max(MIN_SPEED);
+
+        let drag = -0.5 * world.density_at(position) * player.drag_area() * velocity.length() * velocity;
+        velocity += (world.grativy_at(position) + drag / player.mass) * dt;
+
+        let i64_displacement: I64Vec2;
+        (i64_displacement, float_displacement) =
+            to_i64coords_with_rem(float_displacement + velocity * dt);
+        let next_position = position + i64_displacement;
+
+        if let Some(contact) = ground_collision(world, position, next_position) {
+            points.push(contact.point);
+            return Prediction {
+                points,
+                impact: Some(contact.point),
+            };
+        }
+        if world.water_depth(next_position) > 0.0 {
+            points.push(next_position);
+            return Prediction {
+                points,
+                impact: Some(next_position),
+            };
+        }
+        position = next_position;
+        points.push(position);
+    }
+
+    Prediction {
+        points,
+        impact: None,
+    }
+}
+
+#[cfg(test)]
+mod prediction_test {
+    use super::*;
+    use crate::{
+        physics::flight_setup::{fresh_game, world_of},
+        world::terrain::TerrainClass,
+    };
+
+    #[test]
+    fn lob_comes_back_down() {
+        let world = world_of(TerrainClass::Plain);
+        let up = to_meters(world.surface(5) - world.position).normalize();
+        let start = world.surface(5) + to_i64coords(up);
+        let player = Player::new(start);
+
+        let prediction = trace(&world, &[], &player, start, up * 20.0);
+        let impact = prediction.impact.expect("a lob lands");
+        assert!(to_meters(impact - start).length() < 2.0);
+
+        let apex = prediction
+            .points
+            .iter()
+            .map(|point| world.altitude_at(*point) - world.altitude_at(start))
+            .fold(0.0, f32::max);
+        // Drag keeps it well short of the vacuum height of v^2/2g
+        let vacuum = 20.0_f32.powi(2) / (2.0 * world.grativy_at(start).length());
+        assert!(apex > 0.5 * vacuum && apex < vacuum, "apex {apex}");
+        assert!(prediction.points.len() < MAX_STEPS);
+    }
+
+    #[test]
+    fn cached_until_changed() {
+        let mut game = fresh_game();
+        let mut predictor = Predictor::default();
+        predictor.update(&game);
+        let first = predictor.prediction().expect("the starter design lets go").impact;
+        let release = predictor.release;
+
+        game.player.load_beans(100);
+        predictor.update(&game);
+        assert_eq!(predictor.release, release);
+        assert_ne!(predictor.prediction().unwrap().impact, first);
+
+        let SiegeEngine::Trebuchet(trebuchet) = &mut game.engine else {
+            panic!("starts with a trebuchet");
+        };
+        trebuchet.sling.release_angle = 0.3;
+        predictor.update(&game);
+        assert_ne!(predictor.release, release);
+    }
+}
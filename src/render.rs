@@ -1,15 +1,27 @@
-use crate::{game::*, utils::*, GameError};
+use crate::{
+    game::{player::Player, *},
+    launcher::{Launcher, SiegeEngine},
+    physics::prediction::Predictor,
+    utils::*,
+    GameError,
+};
+use floating_arm::draw_floating_arm;
 use hud::draw_hud;
 use macroquad::prelude::*;
-use player::draw_player;
+use mangonel::draw_mangonel;
+use player::{draw_player, draw_shot};
+use prediction::draw_prediction;
 use render_assets::RenderAssets;
 use render_space::RenderSpace;
 use trebuchet::draw_trebuchet;
 use world::draw_world;
 
+mod floating_arm;
 mod hud;
 pub mod icon;
+mod mangonel;
 mod player;
+mod prediction;
 mod render_assets;
 mod render_space;
 mod trebuchet;
@@ -28,6 +40,7 @@ pub struct Render {
     prev_screen:      Vec2,
     smooth_zoom:      Vec2,
     smooth_offset:    Vec2,
+    predictor:        Predictor,
 
     assets: RenderAssets,
 }
@@ -55,6 +68,7 @@ impl Render {
             prev_screen: get_screen(),
             smooth_zoom,
             smooth_offset: Vec2::ZERO,
+            predictor: Predictor::default(),
 
             assets: RenderAssets::init().await?,
         })
@@ -84,13 +98,15 @@ impl Render {
             (true, _) => Vec2::ZERO,
             (false, GameState::Paused) => self.camera.offset,
             (false, GameState::PreLaunch) => vec2(0.0, 0.5),
+            (false, GameState::Launched) if !game.engine.released() => vec2(0.0, 0.5),
             (false, GameState::Launched) => game.player.velocity.normalize() * vec2(-0.5, 0.5),
             (false, GameState::Landed) => vec2(0.0, 0.5),
         };
 
         self.camera.zoom += (self.smooth_zoom - self.camera.zoom) / 8.0;
         self.camera.offset += (self.smooth_offset - self.camera.offset) / 128.0;
-        self.camera.rotation = -to_meters(game.world.position - self.render_space.position)
+        let body = game.body_at(self.render_space.position);
+        self.camera.rotation = -to_meters(body.position - self.render_space.position)
             .perp()
             .to_angle()
             .to_degrees();
@@ -120,10 +136,14 @@ impl Render {
             self.render_space.position =
                 to_i64coords(polar_to_cartesian(freecam_pos.x, freecam_pos.y))
                     + game.world.position;
+        } else if game.replay.cursor.is_some() {
+            self.render_space.position = game.engine.position();
         } else {
             self.render_space.position = game.player.position;
         };
 
+        self.predictor.update(game);
+
         if self.prev_screen != get_screen() {
             self.prev_screen = get_screen();
             // Reset camera
@@ -145,13 +165,37 @@ impl Render {
         //Draw & Clear Background
         clear_background(SKYBLUE);
 
-        draw_player(&self.render_space, &game.player, &self.assets);
-        draw_world(
-            &self.render_space,
-            &game.world,
-            &self.assets.terrain_material,
-        );
-        draw_trebuchet(&self.render_space, &game.trebuchet, &self.assets);
+        match game.replay.current() {
+            Some(frame) => {
+                draw_player(&self.render_space, &Player::new(frame.projectile), &self.assets)
+            }
+            None => draw_player(&self.render_space, &game.player, &self.assets),
+        }
+        for shot in game.shots.iter().filter(|shot| self.render_space.within(shot.position)) {
+            draw_shot(&self.render_space, shot);
+        }
+        for world in std::iter::once(&game.world).chain(&game.moons) {
+            draw_world(&self.render_space, world, &self.assets.terrain_material);
+        }
+        match &game.engine {
+            SiegeEngine::Trebuchet(trebuchet) => match game.replay.current() {
+                Some(frame) => {
+                    let mut posed = trebuchet.clone();
+                    posed.pose(frame);
+                    draw_trebuchet(&self.render_space, &posed, &self.assets)
+                }
+                None => draw_trebuchet(&self.render_space, trebuchet, &self.assets),
+            },
+            SiegeEngine::Mangonel(mangonel) => draw_mangonel(&self.render_space, mangonel),
+            SiegeEngine::FloatingArm(engine) => draw_floating_arm(&self.render_space, engine),
+        }
+
+        if matches!(game.state, GameState::PreLaunch | GameState::Launched) {
+            if let Some(prediction) = self.predictor.prediction() {
+                let pixel = 2.0 / (self.camera.zoom.x * screen_width());
+                draw_prediction(&self.render_space, prediction, pixel);
+            }
+        }
 
         // self.render_space.draw();
 
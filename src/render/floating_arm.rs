@@ -0,0 +1,24 @@
+use super::render_space::RenderSpace;
+use crate::floating_arm::FloatingArm;
+use macroquad::prelude::*;
+
+pub fn draw_floating_arm(render_space: &RenderSpace, engine: &FloatingArm) {
+    if !render_space.within(engine.position) {
+        return;
+    }
+
+    let base = render_space.to_screen(engine.position);
+    let weight = engine.weight_point() + base;
+    let axle = engine.axle_point() + base;
+    let cup = engine.cup_point() + base;
+    let track = base.y + engine.height;
+
+    // Weight guide and the track the axle rolls along
+    draw_line(base.x, base.y, base.x, track + engine.short_length, 0.08, BROWN);
+    draw_line(base.x - engine.long_length, track, base.x + engine.short_length, track, 0.06, BROWN);
+
+    draw_line(weight.x, weight.y, cup.x, cup.y, 0.06, DARKBROWN);
+    draw_circle_lines(cup.x, cup.y, 0.15, 0.03, DARKBROWN);
+    draw_circle(axle.x, axle.y, 0.08, DARKGRAY);
+    draw_rectangle(weight.x - 0.2, weight.y - 0.2, 0.4, 0.4, DARKGRAY);
+}
@@ -1,5 +1,12 @@
 use super::render_assets::RenderAssets;
-use crate::{Game, GameState};
+use crate::{
+    orbit::{OrbitalElements, Trajectory},
+    physics::{warp_factor, Input, WARP_LEVELS},
+    player::AMBIENT_TEMPERATURE,
+    stats::Outcome,
+    upgrades::{BrakeState, Brakes},
+    Game, GameState,
+};
 use macroquad::prelude::*;
 
 pub fn draw_hud(game: &Game, assets: &RenderAssets) {
@@ -61,10 +68,103 @@ pub fn draw_hud(game: &Game, assets: &RenderAssets) {
                 ..Default::default()
             };
 
+            let body = game.body_at(game.player.position);
             let speed = format!("{:.2}m/s", game.player.velocity.length());
-            let altitude = format!("{:.0}m", game.world.altitude_at(game.player.position));
+            let altitude = format!("{:.0}m", body.altitude_at(game.player.position));
             draw_text_ex(&speed, margin_x, margin_y + 24.0, speed_params);
-            draw_text_ex(&altitude, margin_x, margin_y + 60.0, altitude_params);
+            draw_text_ex(&altitude, margin_x, margin_y + 60.0, altitude_params.clone());
+
+            // Heat gauge fills toward the coating's limit
+            let heat = ((game.player.temperature - AMBIENT_TEMPERATURE)
+                / (game.player.heat_limit() - AMBIENT_TEMPERATURE))
+                .clamp(0.0, 1.0);
+            let gauge = Rect::new(margin_x, margin_y + 72.0, screen_width() / 6.0, 16.0);
+            let heat_color = match heat {
+                h if h > 0.75 => RED,
+                h if h > 0.4 => ORANGE,
+                _ => YELLOW,
+            };
+            draw_rectangle(gauge.x, gauge.y, gauge.w * heat, gauge.h, heat_color);
+            draw_rectangle_lines(gauge.x, gauge.y, gauge.w, gauge.h, 2.0, WHITE);
+            let temperature = format!("{:.0}K", game.player.temperature);
+            draw_text_ex(
+                &temperature,
+                gauge.right() + margin_x,
+                gauge.bottom(),
+                altitude_params.clone(),
+            );
+
+            let orbit = OrbitalElements::around(body, game.player.position, game.player.velocity);
+            let km = |radius: f32| (radius - body.radius) / 1000.0;
+            let trajectory = match (orbit.trajectory(body), orbit.apoapsis, orbit.period) {
+                (Trajectory::Escape, ..) => "Escape".to_owned(),
+                (Trajectory::Orbit, Some(apoapsis), Some(period)) => format!(
+                    "Orbit Pe {:.0}km Ap {:.0}km T {:.0}min",
+                    km(orbit.periapsis),
+                    km(apoapsis),
+                    period / 60.0
+                ),
+                (_, Some(apoapsis), _) => format!("Suborbital Ap {:.1}km", km(apoapsis)),
+                (_, None, _) => "Suborbital".to_owned(),
+            };
+            draw_text_ex(&trajectory, margin_x, gauge.bottom() + 36.0, altitude_params.clone());
+            // Semi-major axis from the center, negative once escaping
+            let elements = format!(
+                "a {:.0}km e {:.3}",
+                orbit.semi_major_axis / 1000.0,
+                orbit.eccentricity
+            );
+            draw_text_ex(&elements, margin_x, gauge.bottom() + 72.0, altitude_params.clone());
+
+            let selected = WARP_LEVELS[game.warp];
+            let warp = match warp_factor(game, Input::from_keys()) {
+                factor if factor < selected => format!("Warp {}x ({}x)", factor, selected),
+                factor => format!("Warp {}x", factor),
+            };
+            draw_text_ex(&warp, margin_x, gauge.bottom() + 108.0, altitude_params.clone());
+
+            if game.player.thrusters.is_some() {
+                let fuel = format!(
+                    "Fuel {:.0}g dv {:.0}m/s",
+                    game.player.fuel * 1000.0,
+                    game.player.delta_v()
+                );
+                draw_text_ex(&fuel, margin_x, gauge.bottom() + 144.0, altitude_params.clone());
+            }
+
+            let brakes = match (game.player.brakes, game.player.brake_state) {
+                (None, _) => None,
+                (Some(_), BrakeState::Stowed) => Some("Brakes ready (Space)".to_owned()),
+                (Some(Brakes::Parachute), BrakeState::Deployed) => Some("Chute out".to_owned()),
+                (Some(Brakes::Rocket), BrakeState::Deployed) => {
+                    Some(format!("Retro {:.1}s", game.player.retro_burn))
+                }
+                (Some(_), BrakeState::Torn) => Some("Chute torn!".to_owned()),
+                (Some(_), BrakeState::Spent) => Some("Retro spent".to_owned()),
+            };
+            if let Some(brakes) = brakes {
+                draw_text_ex(&brakes, margin_x, gauge.bottom() + 180.0, altitude_params);
+            }
+        }
+
+        GameState::Landed if game.replay.cursor.is_some() => {
+            let release = game.replay.release_time().unwrap_or(f32::INFINITY);
+            let text = match game.replay.cursor {
+                Some(cursor) if cursor >= release => format!("{:.3}s released", cursor),
+                Some(cursor) => format!("{:.3}s", cursor),
+                None => unreachable!(),
+            };
+            draw_text_ex(
+                &text,
+                screen_width() / 64.0,
+                screen_height() / 32.0 + 24.0,
+                TextParams {
+                    font: Some(&assets.font),
+                    font_size: 48,
+                    color: WHITE,
+                    ..Default::default()
+                },
+            );
         }
 
         GameState::Landed => {
@@ -78,7 +178,9 @@ pub fn draw_hud(game: &Game, assets: &RenderAssets) {
 
             let margin_x = screen_width() / 4.0;
             let margin_y = screen_height() / 4.0;
-            let spacing = 60.0;
+            let stats = game.stats.as_vec();
+            // Squeeze lines together when there are too many stats for the screen
+            let spacing = 60.0_f32.min((screen_height() - 2.0 * margin_y) / (stats.len() + 2) as f32);
             let params = TextParams {
                 font: Some(&assets.font),
                 font_size: 48,
@@ -86,7 +188,7 @@ pub fn draw_hud(game: &Game, assets: &RenderAssets) {
                 ..Default::default()
             };
 
-            for (i, stat) in game.stats.as_vec().iter().enumerate() {
+            for (i, stat) in stats.iter().enumerate() {
                 let stat_fmt = format!("{:.2}{}", stat.value, stat.unit);
                 let stat_width = measure_text(&stat_fmt, Some(&assets.font), 48, 1.0).width;
                 draw_text_ex(
@@ -103,6 +205,28 @@ pub fn draw_hud(game: &Game, assets: &RenderAssets) {
                 );
             }
 
+            let headline = match (game.stats.breakage, game.stats.outcome) {
+                (Some(breakage), _) => Some((breakage.to_string(), RED)),
+                (None, Some(outcome @ Outcome::BurnedUp)) => Some((outcome.to_string(), RED)),
+                (None, Some(outcome @ (Outcome::Orbit | Outcome::Escape | Outcome::MoonLanding))) => {
+                    Some((outcome.to_string(), GOLD))
+                }
+                (None, Some(outcome)) => Some((outcome.to_string(), SKYBLUE)),
+                (None, None) => None,
+            };
+            if let Some((text, color)) = headline {
+                let text_width = measure_text(&text, Some(&assets.font), 48, 1.0).width;
+                draw_text_ex(
+                    &text,
+                    (screen_width() - text_width) / 2.0,
+                    margin_y - spacing,
+                    TextParams {
+                        color,
+                        ..params.clone()
+                    },
+                );
+            }
+
             let re = "Research Earned";
             let points = game.stats.crunch().to_string();
             let re_width = measure_text(re, Some(&assets.font), 48, 1.0).width;
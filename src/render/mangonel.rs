@@ -0,0 +1,23 @@
+use super::render_space::RenderSpace;
+use crate::mangonel::Mangonel;
+use macroquad::prelude::*;
+
+pub fn draw_mangonel(render_space: &RenderSpace, mangonel: &Mangonel) {
+    if !render_space.within(mangonel.position) {
+        return;
+    }
+
+    let base = render_space.to_screen(mangonel.position);
+    let pivot = vec2(base.x, base.y + mangonel.height);
+    let cup = mangonel.cup_point() + pivot;
+    let stop = Vec2::from_angle(mangonel.stop_angle).rotate(Vec2::Y * mangonel.height) + pivot;
+
+    // Frame legs and the stop bar
+    draw_line(base.x - 0.4, base.y, pivot.x, pivot.y, 0.08, BROWN);
+    draw_line(base.x + 0.4, base.y, pivot.x, pivot.y, 0.08, BROWN);
+    draw_line(base.x + 0.4, base.y, stop.x, stop.y, 0.06, BROWN);
+
+    draw_line(pivot.x, pivot.y, cup.x, cup.y, 0.06, DARKBROWN);
+    draw_circle_lines(cup.x, cup.y, 0.15, 0.03, DARKBROWN);
+    draw_circle(pivot.x, pivot.y, 0.1, DARKGRAY);
+}
@@ -1,5 +1,5 @@
 use super::{render_assets::RenderAssets, render_space::RenderSpace};
-use crate::player::Player;
+use crate::{player::Player, upgrades::Shot};
 use macroquad::prelude::*;
 use std::f32::consts;
 
@@ -21,3 +21,10 @@ pub fn draw_player(render_space: &RenderSpace, player: &Player, assets: &RenderA
     );
     // draw_circle_lines(player_pos.x, player_pos.y, 0.08, 0.01, PINK);
 }
+
+/// Tracer streak trailing behind the round
+pub fn draw_shot(render_space: &RenderSpace, shot: &Shot) {
+    let head = render_space.to_screen(shot.position);
+    let tail = head - shot.velocity.normalize_or_zero() * 0.3;
+    draw_line(head.x, head.y, tail.x, tail.y, 0.03, YELLOW);
+}
@@ -0,0 +1,20 @@
+use super::render_space::RenderSpace;
+use crate::physics::prediction::Prediction;
+use macroquad::prelude::*;
+
+/// Dotted arc of the predicted flight, `pixel` is the size of one screen pixel in world units
+pub fn draw_prediction(render_space: &RenderSpace, prediction: &Prediction, pixel: f32) {
+    for (i, pair) in prediction.points.windows(2).enumerate() {
+        if i % 2 == 1 || !(render_space.within(pair[0]) || render_space.within(pair[1])) {
+            continue;
+        }
+        let a = render_space.to_screen(pair[0]);
+        let b = render_space.to_screen(pair[1]);
+        draw_line(a.x, a.y, b.x, b.y, 2.0 * pixel, WHITE);
+    }
+
+    if let Some(impact) = prediction.impact {
+        let impact = render_space.to_screen(impact);
+        draw_circle_lines(impact.x, impact.y, 8.0 * pixel, 2.0 * pixel, RED);
+    }
+}
@@ -33,7 +33,7 @@ impl RenderAssets {
         for path in textures_to_load {
             let name = path
                 .split('/')
-                .last()
+                .next_back()
                 .and_then(|s| s.strip_suffix(".png"))
                 .unwrap();
             texture_names.push(name.into());
@@ -64,6 +64,13 @@ impl RenderAssets {
         })
     }
 
+    pub fn get_or(&self, texture_name: &str, fallback: &str) -> &Texture2D {
+        match self.texture_names.iter().any(|name| *texture_name == **name) {
+            true => self.get(texture_name),
+            false => self.get(fallback),
+        }
+    }
+
     pub fn get(&self, texture_name: &str) -> &Texture2D {
         let index = self
             .texture_names
@@ -1,30 +1,52 @@
 use super::{render_assets::RenderAssets, render_space::RenderSpace};
-use crate::trebuchet::Trebuchet;
+use crate::{
+    launcher::Breakage,
+    trebuchet::{Trebuchet, TrebuchetMaterial as TM},
+};
 use macroquad::prelude::*;
 
+// Only cardboard has textures so far, other materials tint them
+fn material_tint(material: TM) -> Color {
+    match material {
+        TM::Cardboard => WHITE,
+        TM::Wood1 => color_u8!(230, 190, 140, 255),
+        TM::Wood2 => color_u8!(170, 120, 80, 255),
+        TM::Steel => LIGHTGRAY,
+        TM::Space => color_u8!(170, 150, 255, 255),
+    }
+}
+
 pub fn draw_trebuchet(render_space: &RenderSpace, trebuchet: &Trebuchet, assets: &RenderAssets) {
     if !render_space.within(trebuchet.position) {
         return;
     }
 
-    let base = render_space.to_screen(trebuchet.position);
+    let base = render_space.to_screen(trebuchet.position) + trebuchet.base_offset();
     let a_pivot = vec2(base.x, base.y + trebuchet.height);
     let w_position = trebuchet.weight_point() + a_pivot;
     let w_pivot = trebuchet.armweight_point() + a_pivot;
     let s_position = trebuchet.sling_point() + a_pivot;
     let s_pivot = trebuchet.armsling_point() + a_pivot;
 
-    draw_line(
-        s_position.x,
-        s_position.y,
-        s_pivot.x,
-        s_pivot.y,
-        0.005,
-        GRAY,
-    );
+    let tint = material_tint(trebuchet.material);
+
+    if trebuchet.breakage() != Some(Breakage::SlingSnapped) {
+        draw_line(
+            s_position.x,
+            s_position.y,
+            s_pivot.x,
+            s_pivot.y,
+            0.005,
+            GRAY,
+        );
+    }
 
     {
-        let arm_texture = assets.get(&trebuchet.arm.texture());
+        let arm_texture = assets.get_or(&trebuchet.arm.texture(), "cardboard_arm");
+        let arm_tint = match trebuchet.breakage() {
+            Some(Breakage::ArmSnapped) => RED,
+            _ => tint,
+        };
         // Texture is extended by 1/16 for portion past weight pivot
         let arm_size = Vec2::splat(trebuchet.arm.total_length()) * vec2(17.0 / 16.0, 1.0 / 8.0);
         let arm_params = DrawTextureParams {
@@ -37,14 +59,14 @@ pub fn draw_trebuchet(render_space: &RenderSpace, trebuchet: &Trebuchet, assets:
             arm_texture,
             a_pivot.x - a_pivot.distance(s_pivot),
             a_pivot.y - arm_size.y / 2.0,
-            WHITE,
+            arm_tint,
             arm_params,
         );
         // draw_line(s_pivot.x, s_pivot.y, w_pivot.x, w_pivot.y, 0.005, YELLOW);
     }
 
     {
-        let weight_texture = assets.get(&trebuchet.weight.texture());
+        let weight_texture = assets.get_or(&trebuchet.weight.texture(), "cardboard_weight");
         // Texture assumed square and extends at all sides by half
         let weight_size = Vec2::splat(trebuchet.weight.length) * vec2(2.0, 2.0);
         let weight_params = DrawTextureParams {
@@ -58,7 +80,7 @@ pub fn draw_trebuchet(render_space: &RenderSpace, trebuchet: &Trebuchet, assets:
             weight_texture,
             w_pivot.x - weight_size.x / 2.0,
             w_pivot.y - weight_size.y / 4.0,
-            WHITE,
+            tint,
             weight_params,
         );
         // draw_line(
@@ -72,7 +94,7 @@ pub fn draw_trebuchet(render_space: &RenderSpace, trebuchet: &Trebuchet, assets:
     }
 
     {
-        let base_texture = assets.get(&trebuchet.texture());
+        let base_texture = assets.get_or(&trebuchet.texture(), "cardboard_base");
         // Texture extends both sides vertically by 1/8
         let base_size = Vec2::splat(trebuchet.height) * vec2(2.0, 5.0 / 4.0);
         let base_params = DrawTextureParams {
@@ -85,11 +107,20 @@ pub fn draw_trebuchet(render_space: &RenderSpace, trebuchet: &Trebuchet, assets:
             base_texture,
             base.x - base_size.x / 2.0,
             base.y - base_size.y / 10.0,
-            WHITE,
+            tint,
             base_params,
         );
         // draw_line(base.x, base.y, a_pivot.x, a_pivot.y, 0.005, BROWN);
     }
+
+    if let Some(wheels) = trebuchet.wheels {
+        let radius = trebuchet.height / 8.0;
+        for side in [-1.0, 1.0] {
+            let hub = base + wheels.tangent * side * trebuchet.height * 0.75 + wheels.tangent.perp() * radius;
+            draw_circle_lines(hub.x, hub.y, radius, 0.02, DARKGRAY);
+            draw_circle(hub.x, hub.y, radius / 4.0, DARKGRAY);
+        }
+    }
     
     // let p = self.v_projectile() + s;
     // draw_line(s.x, s.y, p.x, p.y, 0.05, PINK);
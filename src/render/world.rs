@@ -6,6 +6,11 @@ const TERRAIN_DEPTH: f32 = -50_000.0;
 const MAX_SEA_DEPTH: f32 = -10_000.0;
 
 pub fn draw_world(render_space: &RenderSpace, world: &World, material: &Material) {
+    // Nothing of a body this far off would make it on screen
+    if world.altitude_at(render_space.position) > render_space.radius {
+        return;
+    }
+
     let circ = world.height_map.len();
     let terrain_idx = world.terrain_index_beneath(render_space.position);
 
@@ -51,6 +56,11 @@ pub fn draw_world(render_space: &RenderSpace, world: &World, material: &Material
         return
     }
 
+    let edge_color = match world.class {
+        WorldClass::Barren => vec4(0.6, 0.6, 0.6, 1.0),
+        _ => vec4(0.253, 0.924, 0.039, 1.0),
+    };
+
     for index in active_indicies.iter() {
         let next_index = (index + 1) % circ;
 
@@ -61,7 +71,7 @@ pub fn draw_world(render_space: &RenderSpace, world: &World, material: &Material
         let bottom_b = world.point_from_sealevel(next_index, TERRAIN_DEPTH);
 
         gl_use_material(material);
-        material.set_uniform("EdgeColor", vec4(0.253, 0.924, 0.039, 1.0));
+        material.set_uniform("EdgeColor", edge_color);
         material.set_uniform("InnerColor", vec4(0.273, 0.168, 0.148, 1.0));
         draw_quadrilateral(
             render_space.to_screen(surface_b),
@@ -20,6 +20,7 @@ pub fn to_meters(i64coords: I64Vec2) -> Vec2 {
     i64coords.as_vec2() / 256.0
 }
 
+#[allow(dead_code)]
 pub fn to_angle(coords: Vec2) -> f32 {
     let theta = coords.y.atan2(coords.x);
     theta.rem_euclid(consts::TAU)
@@ -60,7 +61,7 @@ where
 {
     let k1 = dt * f(dt, x);
     let k2 = dt * f(dt, x + 0.5 * k1);
-    let k3 = dt * f(dt, x + 0.5 * k1);
+    let k3 = dt * f(dt, x + 0.5 * k2);
     let k4 = dt * f(dt, x + k3);
     x + (k1 + 2.0 * k2 + 2.0 * k3 + k4) / 6.0
 }
@@ -79,24 +80,53 @@ where
     let k6 = dt
         * f(
             dt,
-            x - (3.0 * k1 + 2.0 * k2 + 12.0 * k3 - 12.0 * k4 + 8.0 * k5) / 7.0,
+            x + (2.0 * k2 + 12.0 * k3 - 12.0 * k4 + 8.0 * k5 - 3.0 * k1) / 7.0,
         );
     x + (7.0 * k1 + 32.0 * k3 + 12.0 * k4 + 32.0 * k5 + 7.0 * k6) / 90.0
 }
 
+/// Solves `a * x = b` by Gaussian elimination with partial pivoting, `a` indexed by row
+pub fn solve_linear<const N: usize>(mut a: [[f32; N]; N], mut b: [f32; N]) -> [f32; N] {
+    for col in 0..N {
+        let pivot = (col..N)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot_row = a[col];
+        for row in col + 1..N {
+            let factor = a[row][col] / pivot_row[col];
+            for (x, p) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                *x -= factor * p;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let sum: f32 = (row + 1..N).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    x
+}
+
 // | |a-b| a-b |
 // | |c-d| c-d |
 // -------------
 //  | a-b c-d |
 pub fn get_intersection(a: I64Vec2, b: I64Vec2, c: I64Vec2, d: I64Vec2) -> Option<I64Vec2> {
-    let xdiff = I64Vec2::new(a.x - b.x, c.x - d.x).as_dvec2();
-    let ydiff = I64Vec2::new(a.y - b.y, c.y - d.y).as_dvec2();
+    // Relative to `a`, products of coordinates far out around a moon overflow i64
+    let (b, c, d) = ((b - a).as_dvec2(), (c - a).as_dvec2(), (d - a).as_dvec2());
+    let xdiff = DVec2::new(-b.x, c.x - d.x);
+    let ydiff = DVec2::new(-b.y, c.y - d.y);
     let div = xdiff.perp_dot(ydiff);
     if div == 0.0 {
         return None;
     }
-    let dets = I64Vec2::new(a.perp_dot(b), c.perp_dot(d)).as_dvec2();
+    let dets = DVec2::new(0.0, c.perp_dot(d));
     let x = dets.perp_dot(xdiff) / div;
     let y = dets.perp_dot(ydiff) / div;
-    Some(I64Vec2::new(x.round() as i64, y.round() as i64))
+    Some(a + I64Vec2::new(x.round() as i64, y.round() as i64))
 }